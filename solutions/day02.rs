@@ -1,45 +1,172 @@
-use std::str::FromStr;
+use std::collections::BTreeMap;
+
+use advent_2019::loader::{load, Source};
 use log::debug;
 
-fn main() -> Result<(), ()> {
+const TARGET: i64 = 19690720;
+
+/// Polynomial over the noun (`program[1]`) and verb (`program[2]`),
+/// keyed by (noun power, verb power).
+#[derive(Debug, Clone, PartialEq)]
+struct Expr(BTreeMap<(u32, u32), i64>);
+
+impl Expr {
+    fn constant(c: i64) -> Self {
+        Expr(BTreeMap::from([((0, 0), c)])).normalized()
+    }
+
+    fn noun() -> Self {
+        Expr(BTreeMap::from([((1, 0), 1)]))
+    }
+
+    fn verb() -> Self {
+        Expr(BTreeMap::from([((0, 1), 1)]))
+    }
+
+    fn normalized(mut self: Self) -> Self {
+        self.0.retain(|_, c| *c != 0);
+        self
+    }
+
+    fn add(self: &Self, other: &Self) -> Self {
+        let mut terms = self.0.clone();
+        for (powers, c) in &other.0 {
+            *terms.entry(*powers).or_default() += c;
+        }
+        Expr(terms).normalized()
+    }
+
+    fn mul(self: &Self, other: &Self) -> Self {
+        let mut terms: BTreeMap<(u32, u32), i64> = Default::default();
+        for ((n1, v1), c1) in &self.0 {
+            for ((n2, v2), c2) in &other.0 {
+                *terms.entry((n1 + n2, v1 + v2)).or_default() += c1 * c2;
+            }
+        }
+        Expr(terms).normalized()
+    }
+
+    fn as_const(self: &Self) -> Option<i64> {
+        match self.0.keys().all(|powers| *powers == (0, 0)) {
+            true => Some(self.0.get(&(0, 0)).copied().unwrap_or(0)),
+            false => None,
+        }
+    }
+
+    /// Returns `(c, a, b)` such that the expression is `c + a * noun + b * verb`.
+    fn linear(self: &Self) -> Option<(i64, i64, i64)> {
+        let mut res = (0, 0, 0);
+        for (powers, c) in &self.0 {
+            match powers {
+                (0, 0) => res.0 = *c,
+                (1, 0) => res.1 = *c,
+                (0, 1) => res.2 = *c,
+                _ => return None,
+            }
+        }
+        Some(res)
+    }
+}
+
+fn main() -> anyhow::Result<()> {
 
     env_logger::init();
 
     let input = include_str!("../inputs/input-02-2019.txt");
 
-    let mut program: Vec<usize> = input
-        .lines()
-        .filter(|s| !s.is_empty())
-        .flat_map(|l| l.split(',').map(|x| usize::from_str(x).unwrap()))
-        .collect::<Vec<usize>>();
+    let program: Vec<usize> = load(Source::Str(input))?;
 
-    program[1] = 12;
-    program[2] = 2;
+    let mut memory = program.clone();
+    memory[1] = 12;
+    memory[2] = 2;
 
-    let prob_1a_answer = process(&mut program);
+    let prob_1a_answer = process(&mut memory);
 
     println!("Problem 1a answer {}", prob_1a_answer);
 
-    'outer: for noun in 0..=99 {
+    match solve(&program, TARGET) {
+        Some((noun, verb)) => println!("Problem 1b answer {}", 100 * noun + verb),
+        None => println!("Problem 1b has no answer"),
+    }
+
+    Ok(())
+}
+
+/// Runs the program with `program[1]` and `program[2]` left as unknowns.
+/// Cells read through an unknown address become opaque (`None`); returns
+/// `None` if `program[0]` ends up opaque or control flow depends on the unknowns.
+fn symbolic_eval(program: &[usize]) -> Option<Expr> {
+    let mut memory: Vec<Option<Expr>> = program.iter().map(|x| Some(Expr::constant(*x as i64))).collect();
+    memory[1] = Some(Expr::noun());
+    memory[2] = Some(Expr::verb());
+
+    let address = |memory: &[Option<Expr>], ptr: usize| -> Option<usize> {
+        usize::try_from(memory.get(ptr)?.as_ref()?.as_const()?).ok()
+    };
+
+    let mut func_ptr = 0;
+
+    loop {
+        match memory.get(func_ptr)?.as_ref()?.as_const()? {
+            opcode @ (1 | 2) => {
+                let x = address(&memory, func_ptr + 1).and_then(|a| memory.get(a)?.clone());
+                let y = address(&memory, func_ptr + 2).and_then(|a| memory.get(a)?.clone());
+                let dest = address(&memory, func_ptr + 3)?;
+                let res = match (x, y) {
+                    (Some(x), Some(y)) if opcode == 1 => Some(x.add(&y)),
+                    (Some(x), Some(y)) => Some(x.mul(&y)),
+                    _ => None,
+                };
+                *memory.get_mut(dest)? = res;
+                func_ptr += 4;
+            }
+            99 => return memory[0].clone(),
+            _ => return None,
+        }
+    }
+}
+
+/// Finds a noun and verb in `0..=99` for which the program leaves `target` in `program[0]`.
+fn solve(program: &[usize], target: i64) -> Option<(usize, usize)> {
+    match symbolic_eval(program).as_ref().and_then(Expr::linear) {
+        Some((c, a, b)) => {
+            debug!("program[0] = {c} + {a} * noun + {b} * verb");
+            solve_linear(c, a, b, target)
+        }
+        None => {
+            debug!("program[0] is not linear in noun/verb, searching");
+            search(program, target)
+        }
+    }
+}
+
+fn solve_linear(c: i64, a: i64, b: i64, target: i64) -> Option<(usize, usize)> {
+    (0..=99).find_map(|noun| {
+        let rest = target - c - a * noun;
+        let verb = match b {
+            0 if rest == 0 => 0,
+            0 => return None,
+            _ if rest % b != 0 => return None,
+            _ => rest / b,
+        };
+        (0..=99).contains(&verb).then_some((noun as usize, verb as usize))
+    })
+}
+
+fn search(program: &[usize], target: i64) -> Option<(usize, usize)> {
+    for noun in 0..=99 {
         for verb in 0..=99 {
-            program = input
-                .lines()
-                .filter(|s| !s.is_empty())
-                .flat_map(|l| l.split(',').map(|x| usize::from_str(x).unwrap()))
-                .collect::<Vec<usize>>();
-
-            program[1] = noun;
-            program[2] = verb;
-
-            if process(&mut program) == 19690720 {
-                println!("Problem 1b answer {noun}{verb}");
-                break 'outer;
+            let mut memory = program.to_vec();
+            memory[1] = noun;
+            memory[2] = verb;
+
+            if process(&mut memory) as i64 == target {
+                return Some((noun, verb));
             }
         }
         debug!("{noun}/99");
     }
-
-    Ok(())
+    None
 }
 
 fn process(program: &mut Vec<usize>) -> usize {
@@ -1,52 +1,40 @@
 use std::str::FromStr;
 
+use advent_2019::geom::{Direction, Point2};
 use nom::{IResult, Parser};
 use nom::bytes::complete::{tag, take};
 use nom::character::complete::digit1;
 use nom::combinator::map_res;
 use nom::multi::separated_list1;
 
-type Point = (i32, i32);
+type Point = Point2;
 
 type Wire = Vec<Point>;
 
-#[derive(Debug, Copy, Clone)]
-enum Dir {
-    L,
-    R,
-    U,
-    D,
-}
-
-fn parse_dir(input: &str) -> IResult<&str, Dir> {
+fn parse_dir(input: &str) -> IResult<&str, Direction> {
     let (input, c) = take(1usize)(input)?;
     match c {
-        "L" => { Ok((input, Dir::L)) }
-        "D" => { Ok((input, Dir::D)) }
-        "U" => { Ok((input, Dir::U)) }
-        "R" => { Ok((input, Dir::R)) }
+        "L" => { Ok((input, Direction::LEFT)) }
+        "D" => { Ok((input, Direction::DOWN)) }
+        "U" => { Ok((input, Direction::UP)) }
+        "R" => { Ok((input, Direction::RIGHT)) }
         _ => unreachable!()
     }
 }
 
-fn parse_move(input: &str) -> IResult<&str, (Dir, i32)> {
+fn parse_move(input: &str) -> IResult<&str, (Direction, i64)> {
     let (input, dir) = parse_dir(input)?;
-    let (input, num) = map_res(digit1, i32::from_str).parse(input)?;
+    let (input, num) = map_res(digit1, i64::from_str).parse(input)?;
     Ok((input, (dir, num)))
 }
 
 fn parse_wire(input: &str) -> IResult<&str, Wire> {
-    let mut point = (0, 0);
-    let mut acc = vec![point.clone()];
+    let mut point = Point::ORIGIN;
+    let mut acc = vec![point];
     let (input, moves) = separated_list1(tag(","), parse_move).parse(input)?;
     for (dir, len) in moves {
-        match dir {
-            Dir::L => { point = (point.0 - len, point.1) }
-            Dir::R => { point = (point.0 + len, point.1) }
-            Dir::U => { point = (point.0, point.1 + len) }
-            Dir::D => { point = (point.0, point.1 - len) }
-        }
-        acc.push(point.clone());
+        point += dir.offset() * len;
+        acc.push(point);
     }
     Ok((input, acc))
 }
@@ -56,14 +44,15 @@ fn get_line_segments(wire: &Wire) -> Vec<(Point, Point)> {
 }
 
 fn intersect(l1: &(Point, Point), l2: &(Point, Point)) -> Option<Point> {
-    let s1 = (l1.1.0 - l1.0.0, l1.1.1 - l1.0.1);
-    let s2 = (l2.1.0 - l2.0.0, l2.1.1 - l2.0.1);
+    let s1 = l1.1 - l1.0;
+    let s2 = l2.1 - l2.0;
+    let d = l1.0 - l2.0;
 
-    let s = (-s1.1 * (l1.0.0 - l2.0.0) + s1.0 * (l1.0.1 - l2.0.1)) as f32 / ((-s2.0 * s1.1 + s1.0 * s2.1) as f32);
-    let t = (s2.0 * (l1.0.1 - l2.0.1) - s2.1 * (l1.0.0 - l2.0.0)) as f32 / ((-s2.0 * s1.1 + s1.0 * s2.1) as f32);
+    let s = (-s1.y * d.x + s1.x * d.y) as f32 / ((-s2.x * s1.y + s1.x * s2.y) as f32);
+    let t = (s2.x * d.y - s2.y * d.x) as f32 / ((-s2.x * s1.y + s1.x * s2.y) as f32);
 
     if s >= 0.0 && s <= 1.0 && t >= 0.0 && t <= 1.0 {
-        return Some((l1.0.0 + (t * s1.0 as f32) as i32, l1.0.1 + (t * s1.1 as f32) as i32));
+        return Some(Point::new(l1.0.x + (t * s1.x as f32) as i64, l1.0.y + (t * s1.y as f32) as i64));
     }
     None
 }
@@ -87,28 +76,27 @@ fn get_intersections(wire1: &Wire, wire2: &Wire) -> Vec<Point> {
     return intersections;
 }
 
-fn distance_along(wire: &Wire, point: &Point) -> Option<u32> {
-    let mut head = (0, 0);
+fn distance_along(wire: &Wire, point: &Point) -> Option<u64> {
+    let mut head = Point::ORIGIN;
 
     let mut dist = 0;
 
     for bend in wire {
-        if bend.0 == head.0 && bend.0 == point.0 {
-            let range = if head.1 < bend.1 { head.1..=bend.1 } else { bend.1..=head.1 };
-            if range.contains(&point.1) {
-                dist = dist + head.1.abs_diff(point.1);
+        if bend.x == head.x && bend.x == point.x {
+            let range = if head.y < bend.y { head.y..=bend.y } else { bend.y..=head.y };
+            if range.contains(&point.y) {
+                dist = dist + head.y.abs_diff(point.y);
                 return Some(dist);
             }
-        } else if bend.1 == head.1 && bend.1 == point.1 {
-            let range = if head.0 < bend.0 { head.0..=bend.0 } else { bend.0..=head.0 };
-            if range.contains(&point.0) {
-                dist = dist + head.0.abs_diff(point.0);
+        } else if bend.y == head.y && bend.y == point.y {
+            let range = if head.x < bend.x { head.x..=bend.x } else { bend.x..=head.x };
+            if range.contains(&point.x) {
+                dist = dist + head.x.abs_diff(point.x);
                 return Some(dist);
             }
         }
-        let diff = head.0.abs_diff(bend.0) + head.1.abs_diff(bend.1);
-        dist = dist + diff;
-        head = bend.clone();
+        dist = dist + head.manhattan(*bend) as u64;
+        head = *bend;
     }
     println!("Could not find point {point:?}");
     None
@@ -128,14 +116,14 @@ fn main() -> anyhow::Result<()> {
     let intersections = get_intersections(&wire1, &wire2);
 
     let prob_1 = intersections.iter().filter_map(|p|
-        if *p == (0, 0) { None } else { Some(p.0 + p.1) }
+        if *p == Point::ORIGIN { None } else { Some(p.manhattan(Point::ORIGIN)) }
     ).min().unwrap();
 
     println!("Answer 1: {prob_1}");
 
     let prob_2 = intersections.iter()
         .filter_map(|p| Some(distance_along(&wire1, p)? + distance_along(&wire2, p)?))
-        .filter(|d| *d > 0u32)
+        .filter(|d| *d > 0u64)
         .min().unwrap();
 
     println!("Answer 2: {prob_2}");
@@ -1,25 +1,45 @@
 use std::ops::Rem;
-use std::str::FromStr;
 
-fn main() -> Result<(), ()> {
+use advent_2019::coverage;
+use advent_2019::loader::{load, Source};
+use advent_2019::symbolic::{explore, solve};
+
+fn main() -> anyhow::Result<()> {
     env_logger::init();
 
     let input = include_str!("../inputs/input-05-2019.txt");
 
-    let mut program: Vec<i32> = input
-        .lines()
-        .filter(|s| !s.is_empty())
-        .flat_map(|l| l.split(',').map(|x| i32::from_str(x).unwrap()))
-        .collect::<Vec<i32>>();
+    let program: Vec<i32> = load(Source::Str(input))?;
 
-    let prob_1_answer = process(&mut program, || 1);
+    let prob_1_answer = process(&mut program.clone(), || 1);
 
     println!("Problem 1 answer {}", prob_1_answer);
 
-    let prob_2_answer = process(&mut program, || 5);
+    let prob_2_answer = process(&mut program.clone(), || 5);
 
     println!("Problem 2 answer {}", prob_2_answer);
 
+    let symbolic_program: Vec<i64> = load(Source::Str(input))?;
+
+    let paths = explore(&symbolic_program, 1_000_000, 16);
+
+    for path in &paths {
+        let outputs = path.state.outputs.iter().map(|o| o.to_string()).collect::<Vec<String>>();
+        println!("{:?} after {} steps, outputs [{}]", path.end, path.state.steps, outputs.join(", "));
+        for constraint in &path.state.constraints {
+            println!("    {constraint}");
+        }
+        if let Some(inputs) = solve(&path.state.constraints, path.state.inputs) {
+            println!("    e.g. inputs {inputs:?}");
+        }
+    }
+
+    if std::env::args().any(|a| a == "--coverage") {
+        let mut coverage = coverage::run(&symbolic_program, 1)?;
+        coverage.merge(&coverage::run(&symbolic_program, 5)?);
+        print!("{}", coverage.listing(&symbolic_program));
+        print!("{}", coverage.lcov(&symbolic_program, "inputs/input-05-2019.txt"));
+    }
 
     Ok(())
 }
@@ -1,54 +1,47 @@
-use std::collections::{HashMap, HashSet};
+use advent_2019::orbits::OrbitMap;
+use anyhow::anyhow;
 
-fn main() -> Result<(), ()> {
+fn main() -> anyhow::Result<()> {
 
     let input = include_str!("../inputs/input-06-2019.txt");
 
-    let mut orbits: HashMap<&str, &str> = Default::default();
+    let orbits = OrbitMap::parse(input)?;
 
-    input.lines().for_each(|s| {
-        let mut spl = s.split(")");
-        let center = spl.next().unwrap();
-        let satellite = spl.next().unwrap();
-        orbits.insert(satellite, center);
-    });
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let from = args.get(1).map(String::as_str).unwrap_or("YOU");
+    let to = args.get(2).map(String::as_str).unwrap_or("SAN");
+    let route = || orbits.path(from, to).ok_or_else(|| anyhow!("No path from {from} to {to}"));
 
-    let prob_1_ans: u32 = orbits.keys().map(|k| count(&orbits, k, "COM")).sum();
+    match args.first().map(String::as_str) {
+        Some("dot") => {
+            print!("{}", orbits.dot(&route()?));
+            return Ok(());
+        }
+        Some("tree") => {
+            print!("{}", orbits.tree(&route()?));
+            return Ok(());
+        }
+        Some("stats") => {
+            print!("{}", orbits.stats());
+            return Ok(());
+        }
+        Some("path") => {
+            let route = route()?;
+            let transfers = orbits.transfers(from, to).map_or("n/a".to_owned(), |t| t.to_string());
+            println!("{}", route.join(" -> "));
+            println!("{} moves, {transfers} transfers", route.len() - 1);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let prob_1_ans = orbits.total_orbits();
 
     println!("Prob 1: {prob_1_ans}");
 
-    let prob_2_ans: u32 = get_route(&orbits, "YOU", "SAN");
+    let prob_2_ans = orbits.transfers("YOU", "SAN").ok_or_else(|| anyhow!("YOU or SAN is not orbiting anything"))?;
 
     println!("Prob 2: {prob_2_ans}");
 
     Ok(())
 }
-
-
-fn count(orbits: &HashMap<&str, &str>, start: &str, dest: &str) -> u32 {
-    let mut curr = start;
-    let mut count = 0;
-    while !curr.eq(dest) {
-        count += 1;
-        curr = orbits.get(curr).unwrap();
-    }
-    return count;
-}
-
-fn get_route(orbits: &HashMap<&str, &str>, start: &str, dest: &str) -> u32{
-    let mut curr = start;
-    let mut visits: HashSet<&str> = Default::default();
-    while !curr.eq("COM") {
-        curr = orbits.get(curr).unwrap();
-        visits.insert(curr);
-    }
-
-    curr = dest;
-    while !curr.eq("COM") {
-        curr = orbits.get(curr).unwrap();
-        if visits.contains(curr) {
-            return count(orbits, start, curr) + count(orbits, dest, curr) - 2;
-        }
-    }
-    0
-}
\ No newline at end of file
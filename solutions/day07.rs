@@ -1,6 +1,6 @@
 use std::ops::Rem;
-use std::str::FromStr;
 
+use advent_2019::loader::{load, Source};
 use itertools::Itertools;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -20,16 +20,12 @@ struct ProgramState {
     send_signal: bool
 }
 
-fn main() -> Result<(), ()> {
+fn main() -> anyhow::Result<()> {
     env_logger::init();
 
     let input = include_str!("../inputs/input-07-2019.txt");
 
-    let program: Vec<i32> = input
-        .lines()
-        .filter(|s| !s.is_empty())
-        .flat_map(|l| l.split(',').map(|x| i32::from_str(x).unwrap()))
-        .collect::<Vec<i32>>();
+    let program: Vec<i32> = load(Source::Str(input))?;
 
 
     let prob_1_answer = (0..5).permutations(5).map(|c| {
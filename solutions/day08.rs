@@ -1,62 +1,34 @@
-use std::str::FromStr;
+use std::path::Path;
 
-use itertools::Itertools;
+use advent_2019::export::save;
+use advent_2019::ocr::read;
+use advent_2019::space_image::{Pixel, SpaceImage};
 
 const WIDTH: usize = 25;
 const HEIGHT: usize = 6;
 
-type Line = Vec<u32>;
-type Layer = Vec<Line>;
 
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
 
-fn main() -> Result<(), ()> {
     let input = include_str!("../inputs/input-08-2019.txt");
 
-    let pic = input.chars()
-        .map(|c| u32::from_str(c.to_string().as_str()).unwrap())
-        .batching(|it| {
-            let res = it.take(WIDTH).collect::<Line>();
-            if res.is_empty() {return None}
-            Some(res)
-        })
-        .batching(|it| {
-            let res = it.take(HEIGHT).collect::<Layer>();
-            if res.is_empty() {return None}
-            Some(res)
-        })
-        .collect::<Vec<Layer>>();
+    // The saved input is one pixel short of its last layer.
+    let pic = SpaceImage::decode_padded(input, WIDTH, HEIGHT)?;
 
-    let min_zero_layer = pic.iter().min_by_key(|l| {count(l, 0)}).unwrap();
+    let ans_1 = pic.checksum();
 
+    println!("Answer 1: {ans_1}");
 
-    let ans_1 = count(min_zero_layer, 1) * count(min_zero_layer, 2);
+    let image = pic.composite();
 
-    println!("Answer 1: {ans_1}");
+    print!("{}", image.render(|pixel| pixel.map_or(' ', Pixel::glyph)));
 
-    let mut work_space = pic[0].clone();
-
-    for layer in pic.iter().skip(1) {
-        for (line_index, line) in layer.iter().enumerate() {
-            for (pixel_index, pixel) in line.iter().enumerate() {
-                match work_space[line_index][pixel_index] {
-                    2 => {
-                        work_space[line_index][pixel_index] = pixel.clone();
-                    }
-                    1 | 0 => {}
-                    _ => unreachable!()
-                }
-            }
-        }
-    }
+    println!("Answer 2: {}", read(&image, |pixel| *pixel == Pixel::WHITE));
 
-    for line in work_space {
-        let s = line.iter().map(|x| if *x == 0 {" "} else {"█"}).join("");
-        println!("{s}");
+    if let Some(path) = std::env::args().nth(1) {
+        save(&image, Path::new(&path), 10, |pixel| pixel.map_or([0, 0, 0], Pixel::color))?;
     }
 
     Ok(())
 }
-
-fn count(layer: &Layer, item: u32) -> usize {
-    layer.iter().map(|line| line.iter().filter(|x| x.eq(&&item)).count()).sum::<usize>()
-}
\ No newline at end of file
@@ -1,6 +1,8 @@
 use std::ops::{Div, Rem};
-use std::str::FromStr;
 
+use advent_2019::coverage;
+use advent_2019::loader::{load, Source};
+use advent_2019::symbolic::{explore, find_input};
 use anyhow::bail;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -54,11 +56,7 @@ fn main() -> anyhow::Result<()> {
 
     let input = include_str!("../inputs/input-09-2019.txt");
 
-    let program: Vec<i64> = input
-        .lines()
-        .filter(|s| !s.is_empty())
-        .flat_map(|l| l.split(',').map(|x| i64::from_str(x).unwrap()))
-        .collect::<Vec<i64>>();
+    let program: Vec<i64> = load(Source::Str(input))?;
 
     let mut state_1 = ProgramState {memory: program.clone(), input: 1, ..Default::default()};
 
@@ -75,6 +73,29 @@ fn main() -> anyhow::Result<()> {
     }
 
     println!("Answer 2: {}", state_2.output);
+
+    let paths = explore(&program, 1_000_000, 16);
+
+    for path in &paths {
+        let outputs = path.state.outputs.iter().map(|o| o.to_string()).collect::<Vec<String>>();
+        println!("{:?} after {} steps, outputs [{}]", path.end, path.state.steps, outputs.join(", "));
+        for constraint in &path.state.constraints {
+            println!("    {constraint}");
+        }
+    }
+
+    match find_input(&paths, state_2.output) {
+        Some((inputs, _)) => println!("Inputs {inputs:?} output {}", state_2.output),
+        None => println!("No inputs output {}", state_2.output),
+    }
+
+    if std::env::args().any(|a| a == "--coverage") {
+        let mut coverage = coverage::run(&program, 1)?;
+        coverage.merge(&coverage::run(&program, 2)?);
+        print!("{}", coverage.listing(&program));
+        print!("{}", coverage.lcov(&program, "inputs/input-09-2019.txt"));
+    }
+
     Ok(())
 }
 
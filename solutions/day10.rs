@@ -1,32 +1,17 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::ops::{Add, Range, Rem};
 
+use advent_2019::geom::Point2;
+use advent_2019::grid::Grid;
 use gcd::Gcd;
 use itertools::Itertools;
 
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
-struct Asteroid {
-    x: i32,
-    y: i32,
-}
-
-
+type Asteroid = Point2;
 
-fn get_asteroids(input: &str) -> HashSet<Asteroid> {
-    let mut asteroids: HashSet<Asteroid> = Default::default();
 
-    input.lines()
-        .enumerate()
-        .for_each(|(y, line)| {
-            line.chars().enumerate()
-                .for_each(|(x, c)| {
-                    if c != '.' {
-                        asteroids.insert(Asteroid { x: x as i32, y: y as i32 });
-                    }
-                })
-        });
 
-    return asteroids;
+fn get_asteroids(input: &str) -> Grid<Asteroid> {
+    Grid::parse(input, |pos, c| (c != '.').then_some(pos))
 }
 
 fn main() -> anyhow::Result<()> {
@@ -36,9 +21,9 @@ fn main() -> anyhow::Result<()> {
     let asteroids = get_asteroids(input);
     let mut counts: HashMap<Asteroid, u32> = Default::default();
 
-    for source in &asteroids {
+    for source in asteroids.values() {
         let mut count = 0;
-        for dest in &asteroids {
+        for dest in asteroids.values() {
             if not_visible(&asteroids, source, &dest) { continue; }
             count += 1;
         }
@@ -51,9 +36,9 @@ fn main() -> anyhow::Result<()> {
 
     let base = ans_1.0;
 
-    let mut angles: HashMap<i32, Vec<&Asteroid>> = Default::default();
+    let mut angles: HashMap<i64, Vec<&Asteroid>> = Default::default();
 
-    for asteroid in &asteroids {
+    for asteroid in asteroids.values() {
         if asteroid == base { continue; }
         let angle = get_angle(base, asteroid);
         match angles.get_mut(&angle) {
@@ -80,7 +65,7 @@ fn main() -> anyhow::Result<()> {
 
     // assert_eq!(*ans_1.1, angles.len() as u32);
 
-    for ast in &asteroids {
+    for ast in asteroids.values() {
         if !not_visible(&asteroids, base, ast) {
             println!("V: {:?}", ast);
         }
@@ -110,18 +95,18 @@ fn main() -> anyhow::Result<()> {
     }
 }
 
-fn get_angle(base: &Asteroid, asteroid: &Asteroid) -> i32 {
+fn get_angle(base: &Asteroid, asteroid: &Asteroid) -> i64 {
     let angle_rad = ((asteroid.y - base.y) as f32).atan2((asteroid.x - base.x) as f32);
     let angle_deg = angle_rad.to_degrees().add(270_f32 + 180.).rem(360_f32);
-    let angle = (angle_deg * 1000000.) as i32;
+    let angle = (angle_deg * 1000000.) as i64;
     angle
 }
 
-fn not_visible(asteroids: &HashSet<Asteroid>, source: &Asteroid, dest: &Asteroid) -> bool {
+fn not_visible(asteroids: &Grid<Asteroid>, source: &Asteroid, dest: &Asteroid) -> bool {
     if dest == source { return true; }
     if dest.x == source.x {
         for y in walk(dest.y, dest.x) {
-            if asteroids.contains(&Asteroid { x: dest.x, y }) {
+            if asteroids.contains(Point2 { x: dest.x, y }) {
                 return true;
             }
         }
@@ -129,13 +114,13 @@ fn not_visible(asteroids: &HashSet<Asteroid>, source: &Asteroid, dest: &Asteroid
     let (left, right) = if source.x < dest.x { (source, dest) } else { (dest, source) };
     let x_diff_full = right.x - left.x;
     let y_diff_full = right.y - left.y;
-    let gcd = (x_diff_full as u32).gcd(y_diff_full.abs() as u32) as i32;
+    let gcd = (x_diff_full as u64).gcd(y_diff_full.unsigned_abs()) as i64;
     let x_diff = x_diff_full / gcd;
     let y_diff = y_diff_full / gcd;
     let mut x = left.x + x_diff;
     let mut y = left.y + y_diff;
     while x < right.x {
-        if asteroids.contains(&Asteroid { x, y }) {
+        if asteroids.contains(Point2 { x, y }) {
             return true;
         }
         x += x_diff;
@@ -144,11 +129,11 @@ fn not_visible(asteroids: &HashSet<Asteroid>, source: &Asteroid, dest: &Asteroid
     false
 }
 
-fn dist(a1: &Asteroid, a2: &Asteroid) -> i32 {
-    a1.x.abs_diff(a2.x) as i32 + a1.y.abs_diff(a2.y) as i32
+fn dist(a1: &Asteroid, a2: &Asteroid) -> i64 {
+    a1.manhattan(*a2)
 }
 
-fn walk(a: i32, b: i32) -> Range<i32> {
+fn walk(a: i64, b: i64) -> Range<i64> {
     if a < b {
         (a + 1)..b
     } else {
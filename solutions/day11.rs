@@ -1,107 +1,12 @@
-use std::collections::HashMap;
-use std::ops::{Div, Rem};
-use std::str::FromStr;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use anyhow::bail;
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum StopCode {
-    RUN,
-    TERM,
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Mode {
-    POSITION,
-    IMMEDIATE,
-    RELATIVE,
-}
-
-
-#[derive(Debug)]
-struct ProgramState {
-    memory: Vec<i64>,
-    func_ptr: usize,
-    input: i64,
-    stop_code: StopCode,
-    output: i64,
-    relative_base: usize,
-}
-
-impl Default for ProgramState {
-    fn default() -> Self {
-        ProgramState {
-            memory: vec![],
-            func_ptr: 0,
-            input: 0,
-            stop_code: StopCode::RUN,
-            output: 0,
-            relative_base: 0,
-        }
-    }
-}
-
-#[derive(Debug, Copy, Clone)]
-enum Facing { UP, DOWN, LEFT, RIGHT }
-
-impl Facing {
-    fn left(self: &Self) -> Self {
-        match self {
-            Facing::UP => { Facing::LEFT }
-            Facing::DOWN => { Facing::RIGHT }
-            Facing::LEFT => { Facing::DOWN }
-            Facing::RIGHT => { Facing::UP }
-        }
-    }
-
-    fn right(self: &Self) -> Self {
-        match self {
-            Facing::UP => { Facing::RIGHT }
-            Facing::DOWN => { Facing::LEFT }
-            Facing::LEFT => { Facing::UP }
-            Facing::RIGHT => { Facing::DOWN }
-        }
-    }
-}
-
-#[derive(Debug, Copy, Clone)]
-enum Color { WHITE, BLACK }
-
-impl Default for Color {
-    fn default() -> Self {
-        Color::BLACK
-    }
-}
-
-impl TryFrom<i64> for Color {
-    type Error = anyhow::Error;
-    fn try_from(value: i64) -> anyhow::Result<Color> {
-        match value {
-            0 => Ok(Color::BLACK),
-            1 => Ok(Color::WHITE),
-            _ => bail!("Bad color {value}")
-        }
-    }
-}
-
-impl From<Color> for i64 {
-    fn from(value: Color) -> Self {
-        match value {
-            Color::WHITE => {1}
-            Color::BLACK => {0}
-        }
-    }
-}
-
-type Position = (i64, i64);
-
-
-struct Robot {
-    state: ProgramState,
-    map: HashMap<Position, Color>,
-    pos: Position,
-    facing: Facing,
-}
+use advent_2019::export::save;
+use advent_2019::grid::Grid;
+use advent_2019::intcode::{process, rewind_to_output};
+use advent_2019::loader::{load, Source};
+use advent_2019::ocr::read;
+use advent_2019::robot::{paint_counts, replay, Color, Robot};
 
 fn main() -> anyhow::Result<()> {
     env_logger::init();
@@ -109,212 +14,136 @@ fn main() -> anyhow::Result<()> {
 
     let input = include_str!("../inputs/input-11-2019.txt");
 
-    let program: Vec<i64> = input
-        .lines()
-        .filter(|s| !s.is_empty())
-        .flat_map(|l| l.split(',').map(|x| i64::from_str(x).unwrap()))
-        .collect::<Vec<i64>>();
+    let program: Vec<i64> = load(Source::Str(input))?;
 
-    let mut robot = Robot {
-        state: ProgramState { memory: program.clone(), ..Default::default() },
-        map: Default::default(),
-        pos: (0, 0),
-        facing: Facing::UP,
-    };
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
 
-    while robot.state.stop_code == StopCode::RUN {
-        let mut color = robot.map.get(&robot.pos).unwrap_or(&Color::BLACK).clone();
-        robot.state.input = color.try_into()?;
-        process(&mut robot.state)?;
-        if robot.state.stop_code == StopCode::TERM {break}
-        color = Color::try_from(robot.state.output)?;
-        robot.map.insert(robot.pos, color);
-        robot.state.input = color.try_into()?;
-        process(&mut robot.state)?;
-        match robot.state.output {
-            0 => {robot.facing = robot.facing.left()}
-            1 => {robot.facing = robot.facing.right()}
-            _ => {bail!("Bad turn")}
+    match args.first().map(String::as_str) {
+        Some("replay") => {
+            let start = if args.get(1).map(String::as_str) == Some("2") {Color::WHITE} else {Color::BLACK};
+            let fps = args.get(2).map(|s| s.parse()).transpose()?.unwrap_or(100.0);
+            return animate(&program, start, fps);
         }
-        robot.pos = match robot.facing {
-            Facing::UP => {(robot.pos.0, robot.pos.1 + 1)}
-            Facing::DOWN => {(robot.pos.0, robot.pos.1 - 1)}
-            Facing::LEFT => {(robot.pos.0 - 1, robot.pos.1)}
-            Facing::RIGHT => {(robot.pos.0 + 1, robot.pos.1)}
+        Some("heatmap") => return heatmap(&program, args.get(1).map(Path::new)),
+        Some("rewind") => {
+            let paint = args.get(1).map(|s| s.parse()).transpose()?.unwrap_or(0);
+            return rewind(&program, paint);
         }
+        _ => {}
     }
 
+    let mut robot = Robot::new(&program, Color::BLACK);
+    robot.run()?;
+
     println!("Ans 1: {}", robot.map.len());
 
-    robot = Robot {
-        state: ProgramState { memory: program.clone(), ..Default::default() },
-        map: Default::default(),
-        pos: (0, 0),
-        facing: Facing::UP,
-    };
+    let mut robot = Robot::new(&program, Color::WHITE);
+    robot.run()?;
 
-    robot.map.insert((0, 0), Color::WHITE);
+    print!("{}", robot.map.render(|color| match color {
+        Some(Color::WHITE) => '█',
+        _ => ' ',
+    }));
 
-    let (mut min_x, mut max_x, mut min_y, mut max_y) = (i64::MAX, i64::MIN, i64::MAX, i64::MIN);
+    println!("Ans 2: {}", read(&robot.map, |color| matches!(color, Color::WHITE)));
 
-    while robot.state.stop_code == StopCode::RUN {
-        let mut color = robot.map.get(&robot.pos).unwrap_or(&Color::BLACK).clone();
-        robot.state.input = color.try_into()?;
-        process(&mut robot.state)?;
-        if robot.state.stop_code == StopCode::TERM {break}
-        color = Color::try_from(robot.state.output)?;
-        robot.map.insert(robot.pos, color);
-        robot.state.input = color.try_into()?;
-        process(&mut robot.state)?;
-        match robot.state.output {
-            0 => {robot.facing = robot.facing.left()}
-            1 => {robot.facing = robot.facing.right()}
-            _ => {bail!("Bad turn")}
-        }
-        robot.pos = match robot.facing {
-            Facing::UP => {(robot.pos.0, robot.pos.1 + 1)}
-            Facing::DOWN => {(robot.pos.0, robot.pos.1 - 1)}
-            Facing::LEFT => {(robot.pos.0 - 1, robot.pos.1)}
-            Facing::RIGHT => {(robot.pos.0 + 1, robot.pos.1)}
-        };
-        min_x = min_x.min(robot.pos.0);
-        max_x = max_x.max(robot.pos.0);
-        min_y = min_y.min(robot.pos.1);
-        max_y = max_y.max(robot.pos.1);
-    }
-
-    for y in (min_y..=max_y).rev() {
-        let mut line = "".to_owned();
-        for x in min_x..=max_x {
-            line.push_str(match robot.map.get(&(x, y)) {
-                Some(Color::WHITE) => {"█"}
-                _ => " "
-            });
-        }
-        println!("{line}");
+    if let Some(path) = args.first() {
+        save(&robot.map, Path::new(path), 10, |color| match color {
+            Some(Color::WHITE) => [255, 255, 255],
+            _ => [0, 0, 0],
+        })?;
     }
 
     Ok(())
 }
 
-fn process(state: &mut ProgramState) -> anyhow::Result<()> {
-    loop {
-        let instr = state.memory[state.func_ptr];
-        match instr.rem(100) {
-            1 | 2 | 7 | 8 => {
-                three_param(state)?;
-                state.func_ptr += 4;
-            }
-            3 => {
-                let dest = get_param_dest(state, 1)?;
-                state.memory[dest] = state.input;
-                state.func_ptr += 2;
-            }
-            4 => {
-                let res = get_param_value(state, 1)?;
-                state.func_ptr += 2;
-                state.output = res;
-                return Ok(());
-            }
-            5 => {
-                let param1 = get_param_value(state, 1)?;
-                let param2 = get_param_value(state, 2)?;
-                if param1 != 0 {
-                    state.func_ptr = param2 as usize;
-                } else {
-                    state.func_ptr += 3;
-                }
-            }
-            6 => {
-                let param1 = get_param_value(state, 1)?;
-                let param2 = get_param_value(state, 2)?;
-                if param1 == 0 {
-                    state.func_ptr = param2 as usize;
-                } else {
-                    state.func_ptr += 3;
-                }
-            }
-            9 => {
-                let param1 = get_param_value(state, 1)?;
-                state.relative_base = if param1.is_negative() {
-                    state.relative_base - param1.wrapping_abs() as usize
-                } else {
-                    state.relative_base + param1 as usize
-                };
-                state.func_ptr += 2;
-            }
-            99 => {
-                state.stop_code = StopCode::TERM;
-                return Ok(());
-            }
-            _ => {
-                println!("Bad instr {instr} at {0}", state.func_ptr);
-                unreachable!()
-            }
-        }
-    }
-}
-
-fn three_param(state: &mut ProgramState) -> anyhow::Result<()> {
-    let func_ptr = state.func_ptr;
-    let opcode = state.memory[func_ptr];
-    let _param3_mode = get_mode(opcode, 3)?;
+/// Runs the part 1 robot with the intcode journal on, then rewinds the
+/// program to the instruction that painted panel number `paint` and runs it
+/// again to show the same colour comes out.
+fn rewind(program: &[i64], paint: usize) -> anyhow::Result<()> {
+    let mut robot = Robot::new(program, Color::BLACK);
+    robot.state.journal = Some(Default::default());
+    robot.run()?;
 
-    let func = match opcode.rem(10) {
-        1 => std::ops::Add::add,
-        2 => std::ops::Mul::mul,
-        7 => |x, y| (x < y) as i64,
-        8 => |x, y| (x == y) as i64,
-        x => {
-            println!("Bad opcode {opcode} val {x}");
-            unreachable!()
-        }
-    };
+    let outputs = robot.state.journal.as_ref().map_or(0, |j| j.outputs.len());
+    if paint * 2 >= outputs {
+        anyhow::bail!("The robot only painted {} panels", outputs / 2);
+    }
+    let painted = robot.state.journal.as_ref().map(|j| j.outputs[paint * 2]);
 
-    let param1 = get_param_value(state, 1)?;
-    let param2 = get_param_value(state, 2)?;
+    rewind_to_output(&mut robot.state, paint * 2);
+    println!("Paint {paint} comes from the instruction at {}", robot.state.func_ptr);
+    println!("Recorded colour {painted:?}, replayed colour {:?}", process(&mut robot.state)?);
 
-    let dest = get_param_dest(state, 3)?;
-    let res = func(param1, param2);
-    state.memory[dest] = res;
     Ok(())
-    // println!("{opcode} {param1} {param2} wrote {res} to {dest}")
 }
 
-fn get_mode(opcode: i64, pos: usize) -> anyhow::Result<Mode> {
-    match opcode.div(10_i64 * 10_i64.pow(pos as u32)).rem(10) {
-        0 => Ok(Mode::POSITION),
-        1 => Ok(Mode::IMMEDIATE),
-        2 => Ok(Mode::RELATIVE),
-        n => bail!("Unrecognized mode [{n}] in opcode [{opcode}]")
+/// Runs the part 1 robot recording every step and shows how often each panel
+/// was painted: 1-9, or + for more. With a `path` the heatmap is also saved
+/// as an image, brighter for panels painted more often.
+fn heatmap(program: &[i64], path: Option<&Path>) -> anyhow::Result<()> {
+    let mut robot = Robot::new(program, Color::BLACK);
+    robot.record();
+    robot.run()?;
+
+    let history = robot.history.unwrap_or_default();
+    let counts = paint_counts(&history);
+
+    print!("{}", counts.render(|count| match count {
+        None => ' ',
+        Some(count @ 1..=9) => char::from_digit(*count as u32, 10).unwrap_or('?'),
+        Some(_) => '+',
+    }));
+
+    let max = counts.values().max().copied().unwrap_or(0);
+    println!("Steps: {}", history.len());
+    println!("Panels painted: {}", counts.len());
+    println!("Panels painted more than once: {}", counts.values().filter(|c| **c > 1).count());
+    if let Some((pos, _)) = counts.iter().find(|(_, count)| **count == max) {
+        println!("Most painted: {pos:?}, {max} times");
     }
-}
 
-fn get_param_value(state: &ProgramState, offset: usize) -> anyhow::Result<i64> {
-    let mode = get_mode(state.memory[state.func_ptr], offset)?;
-    match mode {
-        Mode::IMMEDIATE => { Ok(access(state, state.func_ptr + offset)) }
-        Mode::POSITION => { Ok(access(state, access(state, state.func_ptr + offset) as usize)) }
-        Mode::RELATIVE => { Ok(access(state, (state.relative_base as i64 + access(state, state.func_ptr + offset)) as usize)) }
-        // _ => { bail!("Unsupported mode {mode:?}") }
+    if let Some(path) = path {
+        save(&counts, path, 4, |count| {
+            let heat = (count.copied().unwrap_or(0) * 255 / max.max(1)) as u8;
+            [heat, heat / 3, 0]
+        })?;
     }
+
+    Ok(())
 }
 
-fn get_param_dest(state: &mut ProgramState, offset: usize) -> anyhow::Result<usize> {
-    let mode = get_mode(state.memory[state.func_ptr], offset)?;
-    let result = match mode {
-        Mode::POSITION => { access(state, state.func_ptr + offset) as usize }
-        Mode::RELATIVE => { (state.relative_base as i64 + access(state, state.func_ptr + offset)) as usize }
-        Mode::IMMEDIATE => bail!("Can not write in Immediate mode")
-    };
+/// Records a run starting on a `start` panel, then replays it checking every
+/// step against the rules and drawing the hull with the robot on it.
+fn animate(program: &[i64], start: Color, fps: f64) -> anyhow::Result<()> {
+    let mut robot = Robot::new(program, start);
+    robot.record();
+    robot.run()?;
 
-    if state.memory.len() <= result { state.memory.resize(result + 1, 0) }
-    Ok(result)
-}
+    let history = robot.history.unwrap_or_default();
+    let bounds = robot.map.bounds();
+    let mut next_frame = Instant::now();
 
-fn access(state: &ProgramState, addr: usize) -> i64 {
-    match state.memory.get(addr) {
-        None => { 0 }
-        Some(x) => { *x }
-    }
+    print!("\x1b[2J");
+
+    let map = replay(&history, start, |map, pos, facing| {
+        let mut view: Grid<char> = Grid::sparse();
+        if let Some(bounds) = bounds {
+            view.insert(bounds.min, ' ');
+            view.insert(bounds.max, ' ');
+        }
+        for (pos, color) in map.iter() {
+            view.insert(pos, if *color == Color::WHITE {'█'} else {' '});
+        }
+        view.insert(pos, facing.glyph());
+
+        print!("\x1b[H{}", view.render(|c| *c.unwrap_or(&' ')));
+        next_frame += Duration::from_secs_f64(1.0 / fps);
+        std::thread::sleep(next_frame.saturating_duration_since(Instant::now()));
+        Ok(())
+    })?;
+
+    println!("Replayed {} steps, {} panels painted", history.len(), map.len());
+
+    Ok(())
 }
@@ -1,27 +1,13 @@
 use std::str::FromStr;
 
+use advent_2019::geom::Point3;
 use nom::bytes::complete::{tag, take_until};
 use nom::combinator::map_res;
 use nom::sequence::preceded;
 
-#[derive(Debug)]
-struct Position {
-    x: i64,
-    y: i64,
-    z: i64,
-}
+type Position = Point3;
 
-impl Default for Position {
-    fn default() -> Self {
-        Position {
-            x: 0,
-            y: 0,
-            z: 0,
-        }
-    }
-}
-
-type Velocity = Position;
+type Velocity = Point3;
 
 
 #[derive(Debug)]
@@ -32,40 +18,15 @@ struct Moon {
 
 impl Moon {
     fn total_energy(self: &Self) -> i64 {
-        (self.pos.x.abs()
-            + self.pos.y.abs()
-            + self.pos.z.abs())
-            * (self.vel.x.abs()
-            + self.vel.y.abs()
-            + self.vel.z.abs())
+        self.pos.manhattan(Point3::ORIGIN) * self.vel.manhattan(Point3::ORIGIN)
     }
 
     fn apply_grav(self: &Self, other: &Self) -> Velocity{
-        let mut x = self.vel.x;
-        let mut y = self.vel.y;
-        let mut z = self.vel.z;
-        if self.pos.x < other.pos.x {
-            x += 1;
-        } else if self.pos.x > other.pos.x {
-            x -= 1;
-        }
-        if self.pos.y < other.pos.y {
-            y += 1;
-        } else if self.pos.y > other.pos.y {
-            y -= 1;
-        }
-        if self.pos.z < other.pos.z {
-            z += 1;
-        } else if self.pos.z > other.pos.z {
-            z -= 1;
-        }
-        Velocity{x, y, z}
+        self.vel + (other.pos - self.pos).signum()
     }
 
     fn apply_velocity(self: &mut Self) {
-        self.pos.x += self.vel.x;
-        self.pos.y += self.vel.y;
-        self.pos.z += self.vel.z;
+        self.pos += self.vel;
     }
 }
 
@@ -76,7 +37,7 @@ fn parse_moon(input: &str) -> nom::IResult<&str, Moon> {
     let (input, y) = preceded(tag(", y="), map_res(take_until(", "), i64::from_str))(input)?;
     let (input, z) = preceded(tag(", z="), map_res(take_until(">"), i64::from_str))(input)?;
 
-    Ok((input, Moon { pos: Position { x, y, z }, vel: Default::default() }))
+    Ok((input, Moon { pos: Position::new(x, y, z), vel: Default::default() }))
 }
 
 fn step(moons: &mut Vec<Moon>){
@@ -0,0 +1,32 @@
+use advent_2019::reactions::{parse, Nanofactory, FUEL};
+
+const CARGO_ORE: u64 = 1_000_000_000_000;
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let input = include_str!("../inputs/input-14-2019.txt");
+
+    let factory = Nanofactory::new(parse(input)?)?;
+
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+
+    match args.first().map(String::as_str) {
+        Some("dot") => {
+            print!("{}", factory.dot());
+            return Ok(());
+        }
+        Some("report") => {
+            let fuel = args.get(1).map(|s| s.parse()).transpose()?.unwrap_or(1);
+            print!("{}", factory.report(&factory.produce(FUEL, fuel)?));
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    println!("Answer 1: {}", factory.ore_for_fuel(1)?);
+
+    println!("Answer 2: {}", factory.max_fuel(CARGO_ORE)?);
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use advent_2019::droid::{fill_time, render, shortest_path, Cell, Droid};
+use advent_2019::export::save;
+use advent_2019::geom::Point2;
+use advent_2019::loader::{load, Source};
+use anyhow::Context;
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let input = include_str!("../inputs/input-15-2019.txt");
+
+    let program: Vec<i64> = load(Source::Str(input))?;
+
+    let mut droid = Droid::new(&program);
+    droid.explore()?;
+
+    let oxygen = droid.oxygen.context("Explored the whole area without finding the oxygen system")?;
+    let path = shortest_path(&droid.map, Point2::ORIGIN, oxygen).context("No way to the oxygen system")?;
+
+    print!("{}", render(&droid.map, droid.pos, &path));
+
+    println!("Answer 1: {}", path.len() - 1);
+
+    println!("Answer 2: {}", fill_time(&droid.map, oxygen));
+
+    if let Some(path) = std::env::args().nth(1) {
+        save(&droid.map, Path::new(&path), 8, |cell| match cell {
+            Some(Cell::WALL) => [90, 90, 90],
+            Some(Cell::OPEN) => [230, 230, 230],
+            Some(Cell::OXYGEN) => [60, 120, 220],
+            None => [0, 0, 0],
+        })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use std::ops::Rem;
+
+use anyhow::bail;
+
+use crate::export::{Raster, Rgb};
+use crate::geom::Point2;
+use crate::grid::Grid;
+use crate::intcode::{step, ProgramState, StopCode};
+use crate::strategy::JoystickStrategy;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Tile {
+    EMPTY,
+    WALL,
+    BLOCK,
+    PADDLE,
+    BALL,
+}
+
+impl TryFrom<i64> for Tile {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i64) -> anyhow::Result<Tile> {
+        Ok(match value {
+            0 => Tile::EMPTY,
+            1 => Tile::WALL,
+            2 => Tile::BLOCK,
+            3 => Tile::PADDLE,
+            4 => Tile::BALL,
+            _ => bail!("Bad tile {value}"),
+        })
+    }
+}
+
+impl Tile {
+    pub fn glyph(&self) -> char {
+        match self {
+            Tile::EMPTY => ' ',
+            Tile::WALL => '█',
+            Tile::BLOCK => '▒',
+            Tile::PADDLE => '▬',
+            Tile::BALL => '●',
+        }
+    }
+
+    pub fn color(&self) -> [u8; 3] {
+        match self {
+            Tile::EMPTY => [0, 0, 0],
+            Tile::WALL => [128, 128, 128],
+            Tile::BLOCK => [200, 80, 40],
+            Tile::PADDLE => [60, 120, 220],
+            Tile::BALL => [240, 240, 240],
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Update {
+    Tile(Point2, Tile),
+    Score(i64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Arcade {
+    pub state: ProgramState,
+    pub screen: Grid<Tile>,
+    pub score: i64,
+    pub ball: Option<Point2>,
+    /// Where the ball was drawn before `ball`.
+    pub last_ball: Option<Point2>,
+    pub paddle: Option<Point2>,
+    /// Largest x drawn so far, which is the right hand wall once the screen is up.
+    pub max_x: i64,
+    /// Instructions executed.
+    pub steps: u64,
+    /// Outputs of a screen update that hasn't been completed yet.
+    pending: Vec<i64>,
+}
+
+impl Arcade {
+    pub fn new(program: &[i64]) -> Self {
+        Arcade {
+            state: ProgramState {
+                memory: program.to_vec(),
+                ..Default::default()
+            },
+            screen: Default::default(),
+            score: 0,
+            ball: None,
+            last_ball: None,
+            paddle: None,
+            max_x: 0,
+            steps: 0,
+            pending: vec![],
+        }
+    }
+
+    /// Sets the cabinet to free play, as part 2 asks.
+    pub fn insert_quarters(&mut self) {
+        self.state.memory[0] = 2;
+    }
+
+    /// Runs the game until it draws a tile or changes the score, or returns
+    /// `None` once the program halts.
+    pub fn next_update(&mut self) -> anyhow::Result<Option<Update>> {
+        while self.state.stop_code == StopCode::RUN {
+            if let Some(update) = self.step()? {
+                return Ok(Some(update));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Like [`Arcade::next_update`], asking `strategy` for the joystick whenever
+    /// the game reads it.
+    pub fn next_update_with(&mut self, strategy: &mut dyn JoystickStrategy) -> anyhow::Result<Option<Update>> {
+        while self.state.stop_code == StopCode::RUN {
+            if self.waiting_for_input() {
+                self.state.input = strategy.joystick(self);
+            }
+            if let Some(update) = self.step()? {
+                return Ok(Some(update));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Feeds `joystick` to the game and runs it until it asks for the joystick
+    /// again. Returns `false` once the game is over.
+    pub fn tick(&mut self, joystick: i64) -> anyhow::Result<bool> {
+        self.state.input = joystick;
+        while self.state.stop_code == StopCode::RUN {
+            self.step()?;
+            if self.waiting_for_input() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    pub fn waiting_for_input(&self) -> bool {
+        self.state.memory[self.state.func_ptr].rem(100) == 3
+    }
+
+    fn step(&mut self) -> anyhow::Result<Option<Update>> {
+        let output = step(&mut self.state)?;
+        self.steps += 1;
+        if self.state.stop_code == StopCode::TERM && !self.pending.is_empty() {
+            bail!("Early exit");
+        }
+        let Some(output) = output else {
+            return Ok(None);
+        };
+        self.pending.push(output);
+        let [x, y, value] = self.pending[..] else {
+            return Ok(None);
+        };
+        self.pending.clear();
+
+        if x == -1 && y == 0 {
+            self.score = value;
+            return Ok(Some(Update::Score(value)));
+        }
+
+        let tile = Tile::try_from(value)?;
+        let pos = Point2::new(x, y);
+        self.screen.insert(pos, tile);
+        self.max_x = self.max_x.max(x);
+        match tile {
+            Tile::BALL => self.last_ball = self.ball.replace(pos),
+            Tile::PADDLE => self.paddle = Some(pos),
+            _ => {}
+        }
+        Ok(Some(Update::Tile(pos, tile)))
+    }
+
+    pub fn blocks(&self) -> usize {
+        self.screen.values().filter(|t| t.eq(&&Tile::BLOCK)).count()
+    }
+
+    /// Draws the screen with one glyph per tile, followed by the score.
+    pub fn render(&self) -> String {
+        let mut frame = self.screen.render(|tile| tile.unwrap_or(&Tile::EMPTY).glyph());
+        frame.push_str(&format!("Score: {}\n", self.score));
+        frame
+    }
+
+    /// Draws the screen as a binary PPM image, with each tile `cell` pixels square.
+    pub fn render_ppm(&self, cell: usize) -> Vec<u8> {
+        Raster::from_grid(&self.screen, cell, palette).ppm()
+    }
+}
+
+/// Colour of each tile for image export, with tiles not drawn yet left empty.
+pub fn palette(tile: Option<&Tile>) -> Rgb {
+    tile.unwrap_or(&Tile::EMPTY).color()
+}
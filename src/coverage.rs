@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use crate::disasm::disassemble;
+use crate::intcode::{process, ProgramState, StopCode};
+
+/// Execution counts per instruction address, merged over any number of runs.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    pub hits: BTreeMap<usize, u64>,
+    pub runs: usize,
+}
+
+impl Coverage {
+    /// Takes the counts recorded in `state.executed`.
+    pub fn from_state(state: &ProgramState) -> Self {
+        Coverage {
+            hits: state.executed.clone().unwrap_or_default(),
+            runs: 1,
+        }
+    }
+
+    pub fn merge(&mut self, other: &Coverage) {
+        for (addr, count) in &other.hits {
+            *self.hits.entry(*addr).or_default() += count;
+        }
+        self.runs += other.runs;
+    }
+
+    /// Disassembly of `program` with each instruction's hit count, or `#####` if
+    /// it never ran. Addresses that ran but weren't found statically (such as
+    /// return sites) are disassembled too.
+    pub fn listing(&self, program: &[i64]) -> String {
+        let entries = self.hits.keys().copied().collect::<Vec<usize>>();
+        let mut out = String::new();
+        for instruction in disassemble(program, &entries) {
+            let count = match self.hits.get(&instruction.addr) {
+                Some(count) => count.to_string(),
+                None => "#####".to_owned(),
+            };
+            out.push_str(&format!("{count:>9} | {instruction}\n"));
+        }
+        out
+    }
+
+    /// Summary in lcov tracefile format. lcov lines start at 1, so address
+    /// `n` is reported as line `n + 1`.
+    pub fn lcov(&self, program: &[i64], name: &str) -> String {
+        let entries = self.hits.keys().copied().collect::<Vec<usize>>();
+        let instructions = disassemble(program, &entries);
+        let mut out = format!("TN:\nSF:{name}\n");
+        for instruction in &instructions {
+            let count = self.hits.get(&instruction.addr).copied().unwrap_or(0);
+            out.push_str(&format!("DA:{},{count}\n", instruction.addr + 1));
+        }
+        let hit = instructions.iter().filter(|i| self.hits.contains_key(&i.addr)).count();
+        out.push_str(&format!("LF:{}\nLH:{hit}\nend_of_record\n", instructions.len()));
+        out
+    }
+}
+
+/// Runs `program` to completion with a constant `input`, recording coverage.
+pub fn run(program: &[i64], input: i64) -> anyhow::Result<Coverage> {
+    let mut state = ProgramState {
+        memory: program.to_vec(),
+        input,
+        executed: Some(Default::default()),
+        ..Default::default()
+    };
+    while state.stop_code == StopCode::RUN {
+        process(&mut state)?;
+    }
+    Ok(Coverage::from_state(&state))
+}
@@ -0,0 +1,118 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Rem;
+
+use anyhow::{bail, Context};
+
+use crate::intcode::{get_mode, param_count, Mode};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CellKind {
+    CODE,
+    DATA,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Instruction {
+    pub addr: usize,
+    pub opcode: i64,
+    pub params: Vec<(Mode, i64)>,
+}
+
+impl Instruction {
+    pub fn decode(memory: &[i64], addr: usize) -> anyhow::Result<Instruction> {
+        let opcode = *memory.get(addr).with_context(|| format!("No instruction at {addr}"))?;
+        let params = (1..=param_count(opcode)?)
+            .map(|i| Ok((get_mode(opcode, i)?, memory.get(addr + i).copied().unwrap_or(0))))
+            .collect::<anyhow::Result<Vec<(Mode, i64)>>>()?;
+        if matches!(opcode.rem(100), 1 | 2 | 3 | 7 | 8) && params.last().map(|p| p.0) == Some(Mode::IMMEDIATE) {
+            bail!("Immediate destination in {opcode} at {addr}");
+        }
+        Ok(Instruction { addr, opcode, params })
+    }
+
+    /// Number of cells taken by the instruction and its parameters.
+    pub fn size(&self) -> usize {
+        1 + self.params.len()
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self.opcode.rem(100) {
+            1 => "add",
+            2 => "mul",
+            3 => "in",
+            4 => "out",
+            5 => "jnz",
+            6 => "jz",
+            7 => "lt",
+            8 => "eq",
+            9 => "arb",
+            _ => "hlt",
+        }
+    }
+
+    /// Addresses control can reach next. Jumps through memory or the relative
+    /// base can't be resolved statically and are left out.
+    pub fn successors(&self) -> Vec<usize> {
+        let next = self.addr + self.size();
+        match self.opcode.rem(100) {
+            99 => vec![],
+            5 | 6 => match self.params[1] {
+                (Mode::IMMEDIATE, target) if target >= 0 => vec![next, target as usize],
+                _ => vec![next],
+            },
+            _ => vec![next],
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let params = self
+            .params
+            .iter()
+            .map(|(mode, value)| match mode {
+                Mode::POSITION => format!("[{value}]"),
+                Mode::IMMEDIATE => format!("{value}"),
+                Mode::RELATIVE => format!("[rb{value:+}]"),
+            })
+            .collect::<Vec<String>>();
+        write!(f, "{:>5}: {:<4}{}", self.addr, self.mnemonic(), params.join(", "))
+    }
+}
+
+/// Instructions reachable from address 0 and from `entries`, in address order.
+pub fn disassemble(memory: &[i64], entries: &[usize]) -> Vec<Instruction> {
+    let mut seen = vec![false; memory.len()];
+    let mut pending = vec![0];
+    pending.extend_from_slice(entries);
+    let mut instructions = vec![];
+
+    while let Some(addr) = pending.pop() {
+        if addr >= memory.len() || seen[addr] {
+            continue;
+        }
+        seen[addr] = true;
+        let instruction = match Instruction::decode(memory, addr) {
+            Ok(instruction) => instruction,
+            Err(e) => {
+                log::debug!("Stopped disassembling: {e}");
+                continue;
+            }
+        };
+        pending.extend(instruction.successors());
+        instructions.push(instruction);
+    }
+
+    instructions.sort_by_key(|i| i.addr);
+    instructions
+}
+
+/// Marks every cell belonging to an instruction found by [`disassemble`] as code.
+pub fn classify(memory: &[i64], entries: &[usize]) -> Vec<CellKind> {
+    let mut kinds = vec![CellKind::DATA; memory.len()];
+    for instruction in disassemble(memory, entries) {
+        let end = (instruction.addr + instruction.size()).min(memory.len());
+        kinds[instruction.addr..end].fill(CellKind::CODE);
+    }
+    kinds
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+
+use crate::geom::{Direction, Point2};
+use crate::grid::Grid;
+use crate::intcode::{process, ProgramState};
+use crate::search::{bfs, flood_fill};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Cell {
+    WALL,
+    OPEN,
+    OXYGEN,
+}
+
+impl Cell {
+    pub fn glyph(&self) -> char {
+        match self {
+            Cell::WALL => '█',
+            Cell::OPEN => ' ',
+            Cell::OXYGEN => 'O',
+        }
+    }
+}
+
+/// What the droid reports after being told to move.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Status {
+    WALL,
+    MOVED,
+    FOUND,
+}
+
+impl TryFrom<i64> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i64) -> anyhow::Result<Status> {
+        Ok(match value {
+            0 => Status::WALL,
+            1 => Status::MOVED,
+            2 => Status::FOUND,
+            _ => bail!("Bad status {value}"),
+        })
+    }
+}
+
+fn command(direction: Direction) -> i64 {
+    match direction {
+        Direction::UP => 1,
+        Direction::DOWN => 2,
+        Direction::LEFT => 3,
+        Direction::RIGHT => 4,
+    }
+}
+
+/// The repair droid, mapping the area as it moves. It starts at the origin.
+#[derive(Debug, Clone)]
+pub struct Droid {
+    pub state: ProgramState,
+    pub pos: Point2,
+    pub map: Grid<Cell>,
+    pub oxygen: Option<Point2>,
+}
+
+impl Droid {
+    pub fn new(program: &[i64]) -> Self {
+        let mut map = Grid::sparse();
+        map.insert(Point2::ORIGIN, Cell::OPEN);
+        Droid {
+            state: ProgramState {
+                memory: program.to_vec(),
+                ..Default::default()
+            },
+            pos: Point2::ORIGIN,
+            map,
+            oxygen: None,
+        }
+    }
+
+    /// Tries to move one step, recording what it finds there.
+    pub fn step(&mut self, direction: Direction) -> anyhow::Result<Status> {
+        self.state.input = command(direction);
+        let Some(reply) = process(&mut self.state)? else {
+            bail!("Droid halted at {}", self.pos);
+        };
+        let status = Status::try_from(reply)?;
+        let target = self.pos.step(direction);
+        match status {
+            Status::WALL => {
+                self.map.insert(target, Cell::WALL);
+            }
+            Status::MOVED => {
+                self.map.insert(target, Cell::OPEN);
+                self.pos = target;
+            }
+            Status::FOUND => {
+                self.map.insert(target, Cell::OXYGEN);
+                self.oxygen = Some(target);
+                self.pos = target;
+            }
+        }
+        Ok(status)
+    }
+
+    /// Walks every reachable cell depth first, backing up the way it came
+    /// when there's nothing new around, and ends back at the start.
+    pub fn explore(&mut self) -> anyhow::Result<()> {
+        let mut path: Vec<Direction> = vec![];
+        loop {
+            let unknown = Direction::ALL.into_iter().find(|d| !self.map.contains(self.pos.step(*d)));
+            match unknown {
+                Some(direction) => {
+                    if self.step(direction)? != Status::WALL {
+                        path.push(direction);
+                    }
+                }
+                None => {
+                    let Some(direction) = path.pop() else {
+                        return Ok(());
+                    };
+                    if self.step(direction.reverse())? == Status::WALL {
+                        bail!("Hit a wall backing up to {}", self.pos.step(direction.reverse()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn open_neighbours(map: &Grid<Cell>, pos: Point2) -> impl Iterator<Item = Point2> + '_ {
+    map.neighbours(pos).filter(|(_, cell)| **cell != Cell::WALL).map(|(next, _)| next)
+}
+
+/// Steps from `from` to every open cell of `map` reachable from it.
+pub fn distances(map: &Grid<Cell>, from: Point2) -> HashMap<Point2, u64> {
+    flood_fill(from, |pos| open_neighbours(map, *pos))
+}
+
+/// Cells on a shortest route from `from` to `to`, both included.
+pub fn shortest_path(map: &Grid<Cell>, from: Point2, to: Point2) -> Option<Vec<Point2>> {
+    bfs(from, |pos| open_neighbours(map, *pos), |pos| *pos == to).map(|route| route.nodes)
+}
+
+/// Minutes for oxygen spreading one cell a minute from `from` to fill every
+/// cell it can reach.
+pub fn fill_time(map: &Grid<Cell>, from: Point2) -> u64 {
+    distances(map, from).into_values().max().unwrap_or(0)
+}
+
+/// Draws the map with the droid as `D`, the start as `S` and `path` as dots.
+pub fn render(map: &Grid<Cell>, droid: Point2, path: &[Point2]) -> String {
+    let mut view: Grid<char> = Grid::sparse();
+    for (pos, cell) in map.iter() {
+        view.insert(pos, cell.glyph());
+    }
+    for pos in path {
+        if view.get(*pos) == Some(&' ') {
+            view.insert(*pos, '.');
+        }
+    }
+    view.insert(droid, 'D');
+    view.insert(Point2::ORIGIN, 'S');
+    view.render(|c| *c.unwrap_or(&' '))
+}
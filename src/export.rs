@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+
+use crate::grid::Grid;
+
+pub type Rgb = [u8; 3];
+
+/// Image file formats a grid can be written as.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Format {
+    PPM,
+    PNG,
+    SVG,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> anyhow::Result<Format> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        Ok(match extension.to_ascii_lowercase().as_str() {
+            "ppm" => Format::PPM,
+            "png" => Format::PNG,
+            "svg" => Format::SVG,
+            _ => bail!("Unknown image format for {}, expected .ppm, .png or .svg", path.display()),
+        })
+    }
+}
+
+/// RGB pixels, row by row from the top left.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+}
+
+impl Raster {
+    /// Paints the grid's bounding box with each cell `cell` pixels square, in
+    /// the colour `palette` gives it. Cells not set are passed as `None`.
+    pub fn from_grid<T>(grid: &Grid<T>, cell: usize, palette: impl Fn(Option<&T>) -> Rgb) -> Self {
+        let rows = grid.rows();
+        let width = rows.first().map_or(0, Vec::len) * cell;
+        let mut pixels = Vec::with_capacity(width * rows.len() * cell);
+        for row in &rows {
+            let line = row.iter().flat_map(|value| [palette(*value)].repeat(cell)).collect::<Vec<Rgb>>();
+            for _ in 0..cell {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        Raster {
+            width,
+            height: rows.len() * cell,
+            pixels,
+        }
+    }
+
+    /// Binary PPM (P6).
+    pub fn ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        out
+    }
+
+    /// 8 bit RGB PNG. The image data is stored without compression, which
+    /// keeps the encoder small at the cost of file size.
+    pub fn png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut header = vec![];
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut out, b"IHDR", &header);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(kind.iter().chain(data));
+    out.extend(crc.to_be_bytes());
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// A zlib stream holding `data` in uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        out.extend((block.len() as u16).to_le_bytes());
+        out.extend((!(block.len() as u16)).to_le_bytes());
+        out.extend(block);
+    }
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend((b << 16 | a).to_be_bytes());
+    out
+}
+
+/// SVG with one square per set cell over a background of `palette(None)`.
+pub fn svg<T>(grid: &Grid<T>, cell: usize, palette: impl Fn(Option<&T>) -> Rgb) -> String {
+    let Some(bounds) = grid.bounds() else {
+        return r#"<svg xmlns="http://www.w3.org/2000/svg" width="0" height="0"/>"#.to_owned() + "\n";
+    };
+    let (width, height) = (bounds.width() * cell, bounds.height() * cell);
+    let hex = |[r, g, b]: Rgb| format!("#{r:02x}{g:02x}{b:02x}");
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+    );
+    out.push_str(&format!("<rect width=\"{width}\" height=\"{height}\" fill=\"{}\"/>\n", hex(palette(None))));
+    for (pos, value) in grid.iter() {
+        out.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{cell}\" height=\"{cell}\" fill=\"{}\"/>\n",
+            (pos.x - bounds.min.x) as usize * cell,
+            (pos.y - bounds.min.y) as usize * cell,
+            hex(palette(Some(value)))
+        ));
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Writes `grid` to `path` in the format its extension names, with each cell
+/// `cell` pixels square.
+pub fn save<T>(grid: &Grid<T>, path: &Path, cell: usize, palette: impl Fn(Option<&T>) -> Rgb) -> anyhow::Result<()> {
+    if cell == 0 {
+        bail!("Cell size must be at least 1 pixel");
+    }
+    let bytes = match Format::from_path(path)? {
+        Format::PPM => Raster::from_grid(grid, cell, palette).ppm(),
+        Format::PNG => Raster::from_grid(grid, cell, palette).png(),
+        Format::SVG => svg(grid, cell, palette).into_bytes(),
+    };
+    std::fs::write(path, bytes).with_context(|| format!("Writing {}", path.display()))
+}
@@ -0,0 +1,260 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A point or offset on a plane, with y growing downwards as on screen.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Point2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point2 {
+    pub const ORIGIN: Point2 = Point2 { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Point2 { x, y }
+    }
+
+    /// Distance to `other` moving only along the axes.
+    pub fn manhattan(&self, other: Point2) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// Each coordinate replaced by its sign.
+    pub fn signum(&self) -> Self {
+        Point2::new(self.x.signum(), self.y.signum())
+    }
+
+    pub fn step(&self, direction: Direction) -> Self {
+        *self + direction.offset()
+    }
+
+    /// The four points sharing an edge with this one, in reading order.
+    pub fn neighbours(&self) -> impl Iterator<Item = Point2> {
+        let p = *self;
+        [Direction::UP, Direction::LEFT, Direction::RIGHT, Direction::DOWN]
+            .into_iter()
+            .map(move |d| p.step(d))
+    }
+
+    /// The eight points surrounding this one, diagonals included, in reading order.
+    pub fn neighbours8(&self) -> impl Iterator<Item = Point2> {
+        let p = *self;
+        (-1..=1)
+            .flat_map(|y| (-1..=1).map(move |x| Point2::new(x, y)))
+            .filter(|offset| *offset != Point2::ORIGIN)
+            .map(move |offset| p + offset)
+    }
+}
+
+impl From<(i64, i64)> for Point2 {
+    fn from((x, y): (i64, i64)) -> Self {
+        Point2 { x, y }
+    }
+}
+
+impl Display for Point2 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Add for Point2 {
+    type Output = Point2;
+
+    fn add(self, other: Point2) -> Point2 {
+        Point2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point2 {
+    type Output = Point2;
+
+    fn sub(self, other: Point2) -> Point2 {
+        Point2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<i64> for Point2 {
+    type Output = Point2;
+
+    fn mul(self, factor: i64) -> Point2 {
+        Point2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Neg for Point2 {
+    type Output = Point2;
+
+    fn neg(self) -> Point2 {
+        Point2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Point2 {
+    fn add_assign(&mut self, other: Point2) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point2 {
+    fn sub_assign(&mut self, other: Point2) {
+        *self = *self - other;
+    }
+}
+
+/// A point or offset in space.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point3 {
+    pub const ORIGIN: Point3 = Point3 { x: 0, y: 0, z: 0 };
+
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Point3 { x, y, z }
+    }
+
+    /// Distance to `other` moving only along the axes.
+    pub fn manhattan(&self, other: Point3) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    /// Each coordinate replaced by its sign.
+    pub fn signum(&self) -> Self {
+        Point3::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    /// The six points sharing a face with this one.
+    pub fn neighbours(&self) -> impl Iterator<Item = Point3> {
+        let p = *self;
+        [(-1, 0, 0), (1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1)]
+            .into_iter()
+            .map(move |(x, y, z)| p + Point3::new(x, y, z))
+    }
+}
+
+impl Display for Point3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<x={}, y={}, z={}>", self.x, self.y, self.z)
+    }
+}
+
+impl Add for Point3 {
+    type Output = Point3;
+
+    fn add(self, other: Point3) -> Point3 {
+        Point3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Point3;
+
+    fn sub(self, other: Point3) -> Point3 {
+        Point3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<i64> for Point3 {
+    type Output = Point3;
+
+    fn mul(self, factor: i64) -> Point3 {
+        Point3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Neg for Point3 {
+    type Output = Point3;
+
+    fn neg(self) -> Point3 {
+        Point3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for Point3 {
+    fn add_assign(&mut self, other: Point3) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point3 {
+    fn sub_assign(&mut self, other: Point3) {
+        *self = *self - other;
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Turn {
+    LEFT,
+    RIGHT,
+}
+
+/// A heading on screen, where up is towards smaller y.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Direction {
+    UP,
+    DOWN,
+    LEFT,
+    RIGHT,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::UP, Direction::DOWN, Direction::LEFT, Direction::RIGHT];
+
+    pub fn left(&self) -> Self {
+        match self {
+            Direction::UP => Direction::LEFT,
+            Direction::DOWN => Direction::RIGHT,
+            Direction::LEFT => Direction::DOWN,
+            Direction::RIGHT => Direction::UP,
+        }
+    }
+
+    pub fn right(&self) -> Self {
+        match self {
+            Direction::UP => Direction::RIGHT,
+            Direction::DOWN => Direction::LEFT,
+            Direction::LEFT => Direction::UP,
+            Direction::RIGHT => Direction::DOWN,
+        }
+    }
+
+    pub fn reverse(&self) -> Self {
+        match self {
+            Direction::UP => Direction::DOWN,
+            Direction::DOWN => Direction::UP,
+            Direction::LEFT => Direction::RIGHT,
+            Direction::RIGHT => Direction::LEFT,
+        }
+    }
+
+    pub fn turn(&self, turn: Turn) -> Self {
+        match turn {
+            Turn::LEFT => self.left(),
+            Turn::RIGHT => self.right(),
+        }
+    }
+
+    /// One step in this direction.
+    pub fn offset(&self) -> Point2 {
+        match self {
+            Direction::UP => Point2::new(0, -1),
+            Direction::DOWN => Point2::new(0, 1),
+            Direction::LEFT => Point2::new(-1, 0),
+            Direction::RIGHT => Point2::new(1, 0),
+        }
+    }
+
+    pub fn glyph(&self) -> char {
+        match self {
+            Direction::UP => '^',
+            Direction::DOWN => 'v',
+            Direction::LEFT => '<',
+            Direction::RIGHT => '>',
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use crate::geom::Point2;
+
+/// Smallest rectangle holding every cell set so far, inclusive on both ends.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Bounds {
+    pub min: Point2,
+    pub max: Point2,
+}
+
+impl Bounds {
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    pub fn contains(&self, pos: Point2) -> bool {
+        (self.min.x..=self.max.x).contains(&pos.x) && (self.min.y..=self.max.y).contains(&pos.y)
+    }
+
+    fn extend(&mut self, pos: Point2) {
+        self.min = Point2::new(self.min.x.min(pos.x), self.min.y.min(pos.y));
+        self.max = Point2::new(self.max.x.max(pos.x), self.max.y.max(pos.y));
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Cells<T> {
+    /// Keyed by `(y, x)` so the map iterates in reading order.
+    Sparse(BTreeMap<(i64, i64), T>),
+    /// Row major, starting at (0, 0).
+    Dense { width: usize, height: usize, cells: Vec<Option<T>> },
+}
+
+/// A 2D grid of optional cells, either sparse for drawings of unknown extent
+/// or dense for fixed size pictures and maps.
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+    cells: Cells<T>,
+    bounds: Option<Bounds>,
+    len: usize,
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Grid::sparse()
+    }
+}
+
+impl<T> Grid<T> {
+    /// An empty grid that grows in every direction.
+    pub fn sparse() -> Self {
+        Grid {
+            cells: Cells::Sparse(BTreeMap::new()),
+            bounds: None,
+            len: 0,
+        }
+    }
+
+    /// An empty `width` by `height` grid. Setting a cell outside it panics.
+    pub fn dense(width: usize, height: usize) -> Self {
+        Grid {
+            cells: Cells::Dense {
+                width,
+                height,
+                cells: (0..width * height).map(|_| None).collect(),
+            },
+            bounds: None,
+            len: 0,
+        }
+    }
+
+    /// A dense grid holding `rows`, which should all be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut grid = Grid::dense(width, rows.len());
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, value) in row.into_iter().enumerate() {
+                grid.insert(Point2::new(x as i64, y as i64), value);
+            }
+        }
+        grid
+    }
+
+    /// A dense grid with one cell per character of `input`, leaving out those
+    /// `cell` returns `None` for.
+    pub fn parse(input: &str, mut cell: impl FnMut(Point2, char) -> Option<T>) -> Self {
+        let width = input.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let mut grid = Grid::dense(width, input.lines().count());
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let pos = Point2::new(x as i64, y as i64);
+                if let Some(value) = cell(pos, c) {
+                    grid.insert(pos, value);
+                }
+            }
+        }
+        grid
+    }
+
+    fn index(width: usize, height: usize, Point2 { x, y }: Point2) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+            return None;
+        }
+        Some(y as usize * width + x as usize)
+    }
+
+    pub fn get(&self, pos: Point2) -> Option<&T> {
+        match &self.cells {
+            Cells::Sparse(map) => map.get(&(pos.y, pos.x)),
+            Cells::Dense { width, height, cells } => cells[Self::index(*width, *height, pos)?].as_ref(),
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Point2) -> Option<&mut T> {
+        match &mut self.cells {
+            Cells::Sparse(map) => map.get_mut(&(pos.y, pos.x)),
+            Cells::Dense { width, height, cells } => cells[Self::index(*width, *height, pos)?].as_mut(),
+        }
+    }
+
+    pub fn contains(&self, pos: Point2) -> bool {
+        self.get(pos).is_some()
+    }
+
+    /// Sets a cell, returning what was there before.
+    pub fn insert(&mut self, pos: Point2, value: T) -> Option<T> {
+        let old = match &mut self.cells {
+            Cells::Sparse(map) => map.insert((pos.y, pos.x), value),
+            Cells::Dense { width, height, cells } => {
+                let index = Self::index(*width, *height, pos)
+                    .unwrap_or_else(|| panic!("{pos:?} is outside a {width}x{height} grid"));
+                cells[index].replace(value)
+            }
+        };
+        if old.is_none() {
+            self.len += 1;
+        }
+        match &mut self.bounds {
+            Some(bounds) => bounds.extend(pos),
+            None => self.bounds = Some(Bounds { min: pos, max: pos }),
+        }
+        old
+    }
+
+    /// Clears a cell. The bounds don't shrink.
+    pub fn remove(&mut self, pos: Point2) -> Option<T> {
+        let old = match &mut self.cells {
+            Cells::Sparse(map) => map.remove(&(pos.y, pos.x)),
+            Cells::Dense { width, height, cells } => cells[Self::index(*width, *height, pos)?].take(),
+        };
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+
+    /// Number of cells set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bounding box of every cell set so far, or `None` if nothing was.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// Cells that are set, in reading order: by row from the top, then left to right.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (Point2, &T)> + '_> {
+        match &self.cells {
+            Cells::Sparse(map) => Box::new(map.iter().map(|((y, x), value)| (Point2::new(*x, *y), value))),
+            Cells::Dense { width, cells, .. } => {
+                let width = *width;
+                Box::new(cells.iter().enumerate().filter_map(move |(i, value)| {
+                    Some((Point2::new((i % width) as i64, (i / width) as i64), value.as_ref()?))
+                }))
+            }
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Set cells above, left of, right of and below `pos`, in reading order.
+    pub fn neighbours(&self, pos: Point2) -> impl Iterator<Item = (Point2, &T)> + '_ {
+        pos.neighbours().filter_map(|pos| Some((pos, self.get(pos)?)))
+    }
+
+    /// Set cells among the eight surrounding `pos`, diagonals included, in reading order.
+    pub fn neighbours8(&self, pos: Point2) -> impl Iterator<Item = (Point2, &T)> + '_ {
+        pos.neighbours8().filter_map(|pos| Some((pos, self.get(pos)?)))
+    }
+
+    /// Every row of the bounding box from the top, with `None` for cells not set.
+    pub fn rows(&self) -> Vec<Vec<Option<&T>>> {
+        let Some(bounds) = self.bounds else {
+            return vec![];
+        };
+        (bounds.min.y..=bounds.max.y)
+            .map(|y| (bounds.min.x..=bounds.max.x).map(|x| self.get(Point2::new(x, y))).collect())
+            .collect()
+    }
+
+    /// Draws the bounding box one character per cell, each line ending in a newline.
+    pub fn render(&self, glyph: impl Fn(Option<&T>) -> char) -> String {
+        let mut out = String::new();
+        for row in self.rows() {
+            out.extend(row.into_iter().map(&glyph));
+            out.push('\n');
+        }
+        out
+    }
+}
@@ -0,0 +1,401 @@
+use std::collections::BTreeMap;
+use std::ops::{Div, Rem};
+
+use anyhow::bail;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StopCode {
+    RUN,
+    TERM,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Mode {
+    POSITION,
+    IMMEDIATE,
+    RELATIVE,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProgramState {
+    pub memory: Vec<i64>,
+    pub func_ptr: usize,
+    pub input: i64,
+    pub stop_code: StopCode,
+    pub output: i64,
+    pub relative_base: usize,
+    /// Undo log, only kept when set.
+    pub journal: Option<Journal>,
+    /// Times each address was executed, only kept when set.
+    pub executed: Option<BTreeMap<usize, u64>>,
+}
+
+/// What it takes to revert one executed instruction.
+#[derive(Debug, Clone)]
+pub struct Undo {
+    pub func_ptr: usize,
+    pub relative_base: usize,
+    pub stop_code: StopCode,
+    pub output: i64,
+    pub memory_len: usize,
+    /// Address written and the value it held before.
+    pub write: Option<(usize, i64)>,
+    pub read_input: bool,
+    pub wrote_output: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    pub undo: Vec<Undo>,
+    /// Every input value the program has read, in order.
+    pub inputs: Vec<i64>,
+    pub outputs: Vec<i64>,
+    last_write: Option<(usize, i64)>,
+}
+
+impl Default for ProgramState {
+    fn default() -> Self {
+        ProgramState {
+            memory: vec![],
+            func_ptr: 0,
+            input: 0,
+            stop_code: StopCode::RUN,
+            output: 0,
+            relative_base: 0,
+            journal: None,
+            executed: None,
+        }
+    }
+}
+
+pub fn process(state: &mut ProgramState) -> anyhow::Result<Option<i64>> {
+    loop {
+        if let Some(output) = step(state)? {
+            return Ok(Some(output));
+        }
+        if state.stop_code == StopCode::TERM {
+            return Ok(None);
+        }
+    }
+}
+
+/// Runs up to `steps` instructions, stopping early if the program halts.
+pub fn run_steps(state: &mut ProgramState, steps: usize) -> anyhow::Result<()> {
+    for _ in 0..steps {
+        if state.stop_code == StopCode::TERM {
+            break;
+        }
+        step(state)?;
+    }
+    Ok(())
+}
+
+/// Executes the instruction at `func_ptr`, returning its value if it was an output.
+pub fn step(state: &mut ProgramState) -> anyhow::Result<Option<i64>> {
+    let undo = state.journal.as_ref().map(|_| Undo {
+        func_ptr: state.func_ptr,
+        relative_base: state.relative_base,
+        stop_code: state.stop_code,
+        output: state.output,
+        memory_len: state.memory.len(),
+        write: None,
+        read_input: state.memory[state.func_ptr].rem(100) == 3,
+        wrote_output: state.memory[state.func_ptr].rem(100) == 4,
+    });
+
+    if let Some(executed) = state.executed.as_mut() {
+        *executed.entry(state.func_ptr).or_default() += 1;
+    }
+
+    let res = execute(state)?;
+
+    if let (Some(mut undo), Some(journal)) = (undo, state.journal.as_mut()) {
+        undo.write = journal.last_write.take();
+        if undo.read_input {
+            journal.inputs.push(state.input);
+        }
+        if undo.wrote_output {
+            journal.outputs.push(state.output);
+        }
+        journal.undo.push(undo);
+    }
+
+    Ok(res)
+}
+
+/// Reverts the last instruction recorded in the journal. Returns `false` if
+/// there is no journal or nothing left to undo.
+pub fn step_back(state: &mut ProgramState) -> bool {
+    let Some(journal) = state.journal.as_mut() else {
+        return false;
+    };
+    let Some(undo) = journal.undo.pop() else {
+        return false;
+    };
+    if undo.read_input {
+        journal.inputs.pop();
+    }
+    if undo.wrote_output {
+        journal.outputs.pop();
+    }
+    if let Some((addr, old)) = undo.write {
+        state.memory[addr] = old;
+    }
+    state.memory.truncate(undo.memory_len);
+    state.func_ptr = undo.func_ptr;
+    state.relative_base = undo.relative_base;
+    state.stop_code = undo.stop_code;
+    state.output = undo.output;
+    true
+}
+
+/// Steps back to just before the instruction that wrote output number `index`
+/// (counting from 0), so the next `step` produces it again.
+pub fn rewind_to_output(state: &mut ProgramState, index: usize) -> bool {
+    while state.journal.as_ref().is_some_and(|j| j.outputs.len() > index) {
+        step_back(state);
+    }
+    state.journal.is_some()
+}
+
+/// Re-runs `program` from the start with a journal, feeding it the recorded
+/// `inputs` in order. Stops once it halts or wants more input than was recorded.
+pub fn replay(program: &[i64], inputs: &[i64]) -> anyhow::Result<ProgramState> {
+    let mut state = ProgramState {
+        memory: program.to_vec(),
+        journal: Some(Default::default()),
+        ..Default::default()
+    };
+    let mut inputs = inputs.iter();
+
+    while state.stop_code == StopCode::RUN {
+        if state.memory[state.func_ptr].rem(100) == 3 {
+            match inputs.next() {
+                Some(input) => state.input = *input,
+                None => break,
+            }
+        }
+        step(&mut state)?;
+    }
+
+    Ok(state)
+}
+
+fn execute(state: &mut ProgramState) -> anyhow::Result<Option<i64>> {
+    let instr = state.memory[state.func_ptr];
+    match instr.rem(100) {
+        1 | 2 | 7 | 8 => {
+            three_param(state)?;
+            state.func_ptr += 4;
+        }
+        3 => {
+            let dest = get_param_dest(state, 1)?;
+            write(state, dest, state.input);
+            state.func_ptr += 2;
+        }
+        4 => {
+            let res = get_param_value(state, 1)?;
+            state.func_ptr += 2;
+            state.output = res;
+            return Ok(Some(state.output));
+        }
+        5 => {
+            let param1 = get_param_value(state, 1)?;
+            let param2 = get_param_value(state, 2)?;
+            if param1 != 0 {
+                state.func_ptr = param2 as usize;
+            } else {
+                state.func_ptr += 3;
+            }
+        }
+        6 => {
+            let param1 = get_param_value(state, 1)?;
+            let param2 = get_param_value(state, 2)?;
+            if param1 == 0 {
+                state.func_ptr = param2 as usize;
+            } else {
+                state.func_ptr += 3;
+            }
+        }
+        9 => {
+            let param1 = get_param_value(state, 1)?;
+            state.relative_base = if param1.is_negative() {
+                state.relative_base - param1.wrapping_abs() as usize
+            } else {
+                state.relative_base + param1 as usize
+            };
+            state.func_ptr += 2;
+        }
+        99 => {
+            state.stop_code = StopCode::TERM;
+        }
+        _ => bail!("Bad instr {instr} at {0}", state.func_ptr),
+    }
+    Ok(None)
+}
+
+fn three_param(state: &mut ProgramState) -> anyhow::Result<()> {
+    let func_ptr = state.func_ptr;
+    let opcode = state.memory[func_ptr];
+    let _param3_mode = get_mode(opcode, 3)?;
+
+    let func = match opcode.rem(10) {
+        1 => std::ops::Add::add,
+        2 => std::ops::Mul::mul,
+        7 => |x, y| (x < y) as i64,
+        8 => |x, y| (x == y) as i64,
+        x => bail!("Bad opcode {opcode} val {x}"),
+    };
+
+    let param1 = get_param_value(state, 1)?;
+    let param2 = get_param_value(state, 2)?;
+
+    let dest = get_param_dest(state, 3)?;
+    let res = func(param1, param2);
+    write(state, dest, res);
+    Ok(())
+    // println!("{opcode} {param1} {param2} wrote {res} to {dest}")
+}
+
+fn write(state: &mut ProgramState, dest: usize, value: i64) {
+    if let Some(journal) = state.journal.as_mut() {
+        journal.last_write = Some((dest, state.memory[dest]));
+    }
+    state.memory[dest] = value;
+}
+
+pub fn get_mode(opcode: i64, pos: usize) -> anyhow::Result<Mode> {
+    match opcode.div(10_i64 * 10_i64.pow(pos as u32)).rem(10) {
+        0 => Ok(Mode::POSITION),
+        1 => Ok(Mode::IMMEDIATE),
+        2 => Ok(Mode::RELATIVE),
+        n => bail!("Unrecognized mode [{n}] in opcode [{opcode}]"),
+    }
+}
+
+/// Number of parameters taken by the instruction `opcode`.
+pub fn param_count(opcode: i64) -> anyhow::Result<usize> {
+    Ok(match opcode.rem(100) {
+        1 | 2 | 7 | 8 => 3,
+        5 | 6 => 2,
+        3 | 4 | 9 => 1,
+        99 => 0,
+        _ => bail!("Bad instr {opcode}"),
+    })
+}
+
+fn get_param_value(state: &ProgramState, offset: usize) -> anyhow::Result<i64> {
+    let mode = get_mode(state.memory[state.func_ptr], offset)?;
+    match mode {
+        Mode::IMMEDIATE => Ok(access(state, state.func_ptr + offset)),
+        Mode::POSITION => Ok(access(
+            state,
+            access(state, state.func_ptr + offset) as usize,
+        )),
+        Mode::RELATIVE => Ok(access(
+            state,
+            (state.relative_base as i64 + access(state, state.func_ptr + offset)) as usize,
+        )), // _ => { bail!("Unsupported mode {mode:?}") }
+    }
+}
+
+fn get_param_dest(state: &mut ProgramState, offset: usize) -> anyhow::Result<usize> {
+    let mode = get_mode(state.memory[state.func_ptr], offset)?;
+    let result = match mode {
+        Mode::POSITION => access(state, state.func_ptr + offset) as usize,
+        Mode::RELATIVE => {
+            (state.relative_base as i64 + access(state, state.func_ptr + offset)) as usize
+        }
+        Mode::IMMEDIATE => bail!("Can not write in Immediate mode"),
+    };
+
+    if state.memory.len() <= result {
+        state.memory.resize(result + 1, 0)
+    }
+    Ok(result)
+}
+
+fn access(state: &ProgramState, addr: usize) -> i64 {
+    match state.memory.get(addr) {
+        None => 0,
+        Some(x) => *x,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::{load, Source};
+
+    /// Day 9's example that outputs a copy of itself, writing past the end of
+    /// its memory and moving the relative base as it goes.
+    const QUINE: [i64; 16] = [109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99];
+
+    fn journaled(program: &[i64], input: i64) -> ProgramState {
+        let mut state = ProgramState {
+            memory: program.to_vec(),
+            input,
+            journal: Some(Default::default()),
+            ..Default::default()
+        };
+        while state.stop_code == StopCode::RUN {
+            step(&mut state).unwrap();
+        }
+        state
+    }
+
+    fn day9() -> Vec<i64> {
+        load(Source::Str(include_str!("../inputs/input-09-2019.txt"))).unwrap()
+    }
+
+    #[test]
+    fn undoing_every_step_restores_the_start() {
+        for (program, input) in [(QUINE.to_vec(), 0), (day9(), 1)] {
+            let mut state = journaled(&program, input);
+            assert_ne!(state.func_ptr, 0);
+            while step_back(&mut state) {}
+            assert_eq!(state.memory, program);
+            assert_eq!((state.func_ptr, state.relative_base), (0, 0));
+            assert_eq!(state.stop_code, StopCode::RUN);
+            let journal = state.journal.unwrap();
+            assert!(journal.inputs.is_empty() && journal.outputs.is_empty());
+        }
+    }
+
+    #[test]
+    fn undo_shrinks_grown_memory() {
+        let mut state = journaled(&[3, 100, 4, 100, 99], 42);
+        assert_eq!(state.memory.len(), 101);
+        assert_eq!(state.journal.as_ref().unwrap().outputs, [42]);
+
+        assert!(step_back(&mut state));
+        assert!(step_back(&mut state));
+        assert_eq!(state.memory.len(), 101);
+        assert!(step_back(&mut state));
+        assert_eq!(state.memory, [3, 100, 4, 100, 99]);
+        assert!(!step_back(&mut state));
+    }
+
+    #[test]
+    fn rewinding_reproduces_an_output() {
+        let mut state = journaled(&QUINE, 0);
+        assert_eq!(state.journal.as_ref().unwrap().outputs, QUINE);
+        assert!(rewind_to_output(&mut state, 5));
+        assert_eq!(process(&mut state).unwrap(), Some(QUINE[5]));
+        assert!(rewind_to_output(&mut state, 0));
+        assert_eq!(process(&mut state).unwrap(), Some(QUINE[0]));
+
+        assert!(!rewind_to_output(&mut ProgramState::default(), 0));
+    }
+
+    #[test]
+    fn replay_matches_original_run() {
+        let program = day9();
+        let original = journaled(&program, 2);
+        let journal = original.journal.as_ref().unwrap();
+        let replayed = replay(&program, &journal.inputs).unwrap();
+        assert_eq!(replayed.journal.as_ref().unwrap().outputs, journal.outputs);
+        assert_eq!(replayed.memory, original.memory);
+        assert_eq!(replayed.stop_code, StopCode::TERM);
+    }
+}
@@ -0,0 +1,23 @@
+#![allow(clippy::upper_case_acronyms)]
+
+pub mod arcade;
+pub mod coverage;
+pub mod disasm;
+pub mod droid;
+pub mod export;
+pub mod geom;
+pub mod grid;
+pub mod intcode;
+pub mod loader;
+pub mod memdiff;
+pub mod memory_image;
+pub mod ocr;
+pub mod orbits;
+pub mod reactions;
+pub mod recording;
+pub mod robot;
+pub mod scanner;
+pub mod search;
+pub mod space_image;
+pub mod strategy;
+pub mod symbolic;
@@ -0,0 +1,115 @@
+use std::fmt::Display;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use snafu::{ResultExt, Snafu};
+
+#[derive(Debug, Snafu)]
+pub enum LoadError {
+    #[snafu(display("Could not read {name}: {source}"))]
+    Io { name: String, source: std::io::Error },
+    #[snafu(display("Bad token {token:?} at line {line}, column {column} (token {index}): {reason}"))]
+    BadToken {
+        token: String,
+        line: usize,
+        column: usize,
+        index: usize,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum Source<'a> {
+    Path(&'a Path),
+    Stdin,
+    Str(&'a str),
+}
+
+/// Loads a comma separated intcode program. Tokens may be surrounded by whitespace,
+/// split across lines and followed by a trailing comma at the end of a line.
+/// Lines and columns in errors are 1-based; the token index is the cell's address.
+pub fn load<T>(source: Source) -> Result<Vec<T>, LoadError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match source {
+        Source::Str(input) => parse(input),
+        Source::Path(path) => {
+            let input = std::fs::read_to_string(path).context(IoSnafu { name: path.display().to_string() })?;
+            parse(&input)
+        }
+        Source::Stdin => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input).context(IoSnafu { name: "stdin" })?;
+            parse(&input)
+        }
+    }
+}
+
+fn parse<T>(input: &str) -> Result<Vec<T>, LoadError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let mut program = vec![];
+
+    for (line_index, line) in input.lines().enumerate() {
+        let tokens = line.split(',').collect::<Vec<&str>>();
+        let mut column = 1;
+        for (i, raw) in tokens.iter().enumerate() {
+            let token = raw.trim();
+            let token_column = column + raw.chars().take_while(|c| c.is_whitespace()).count();
+            column += raw.chars().count() + 1;
+            if token.is_empty() && (i + 1 == tokens.len() || tokens.iter().all(|t| t.trim().is_empty())) {
+                continue;
+            }
+            let value = T::from_str(token).map_err(|e| LoadError::BadToken {
+                token: token.to_owned(),
+                line: line_index + 1,
+                column: token_column,
+                index: program.len(),
+                reason: match token.is_empty() {
+                    true => "empty token".to_owned(),
+                    false => e.to_string(),
+                },
+            })?;
+            program.push(value);
+        }
+    }
+
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_where_a_token_is() {
+        let err = load::<i64>(Source::Str("1,2,\n3, x ,4")).unwrap_err();
+        assert!(matches!(
+            &err,
+            LoadError::BadToken { token, line: 2, column: 4, index: 3, .. } if token == "x"
+        ));
+        assert!(err.to_string().starts_with("Bad token \"x\" at line 2, column 4 (token 3): "));
+    }
+
+    #[test]
+    fn accepts_whitespace_and_line_endings() {
+        let program = load::<i64>(Source::Str("  1 , -2,\r\n\t3 ,4\r\n\r\n")).unwrap();
+        assert_eq!(program, [1, -2, 3, 4]);
+        assert_eq!(load::<i64>(Source::Str("1,2,3,\n")).unwrap(), [1, 2, 3]);
+        assert_eq!(load::<i64>(Source::Str("")).unwrap(), []);
+    }
+
+    #[test]
+    fn rejects_empty_tokens() {
+        let err = load::<i64>(Source::Str("1,,2")).unwrap_err();
+        assert!(matches!(
+            &err,
+            LoadError::BadToken { line: 1, column: 3, index: 1, reason, .. } if reason == "empty token"
+        ));
+    }
+}
@@ -1,288 +1,439 @@
-use std::collections::HashMap;
-use std::ops::{Div, Rem};
-use std::str::FromStr;
-
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use advent_2019::arcade::{palette, Arcade, Tile, Update};
+use advent_2019::disasm::classify;
+use advent_2019::export::save;
+use advent_2019::geom::Point2;
+use advent_2019::intcode::{run_steps, ProgramState, StopCode};
+use advent_2019::loader::{load, Source};
+use advent_2019::memdiff::diff;
+use advent_2019::recording::{Asciicast, FrameDir};
+use advent_2019::scanner::{Predicate, Scanner};
+use advent_2019::strategy::{FollowBall, JoystickStrategy, PredictLanding, Random};
 use anyhow::{bail, Context};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum StopCode {
-    RUN,
-    TERM,
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let input = include_str!("../inputs/input-13-2019.txt");
+
+    let program: Vec<i64> = load(Source::Str(input))?;
+
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+
+    match args.first().map(String::as_str) {
+        Some("diff") => {
+            let steps = args.get(1).map(|s| s.parse()).transpose()?.unwrap_or(10_000);
+            diff_joystick(&program, steps)
+        }
+        Some("scan") => scan(&program),
+        Some("render") => match args.get(1).map(String::as_str) {
+            Some("final") => render(&program, None),
+            _ => render(&program, Some(fps_arg(&args, 1, 30.0)?)),
+        },
+        Some("play") => play(&program, fps_arg(&args, 1, 10.0)?),
+        Some("benchmark") => benchmark(&program),
+        Some("screenshot") => {
+            let path = Path::new(args.get(1).context("Usage: screenshot FILE.{ppm,png,svg} [cell]")?);
+            screenshot(&program, path, count_arg(&args, 2, 8, "cell")?)
+        }
+        Some("record") => {
+            let usage = "Usage: record cast FILE [fps] | record frames DIR [every] [cell]";
+            let path = Path::new(args.get(2).context(usage)?);
+            match args[1].as_str() {
+                "cast" => record_cast(&program, path, fps_arg(&args, 3, 30.0)?),
+                "frames" => {
+                    record_frames(&program, path, count_arg(&args, 3, 1, "every")?, count_arg(&args, 4, 4, "cell")?)
+                }
+                _ => bail!(usage),
+            }
+        }
+        _ => solve(&program),
+    }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Mode {
-    POSITION,
-    IMMEDIATE,
-    RELATIVE,
+/// Argument `i` as a positive whole number, named `what` in errors.
+fn count_arg(args: &[String], i: usize, default: usize, what: &str) -> anyhow::Result<usize> {
+    let Some(arg) = args.get(i) else {
+        return Ok(default);
+    };
+    match arg.parse() {
+        Ok(0) | Err(_) => bail!("{what} must be a positive whole number, got {arg:?}"),
+        Ok(n) => Ok(n),
+    }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Tile {
-    EMPTY,
-    WALL,
-    BLOCK,
-    PADDLE,
-    BALL,
+/// Frames per second from argument `i`, which must be positive, finite and give a
+/// representable frame time.
+fn fps_arg(args: &[String], i: usize, default: f64) -> anyhow::Result<f64> {
+    let Some(arg) = args.get(i) else {
+        return Ok(default);
+    };
+    match arg.parse::<f64>() {
+        Ok(fps) if fps.is_finite() && fps > 0.0 && Duration::try_from_secs_f64(1.0 / fps).is_ok() => Ok(fps),
+        _ => bail!("fps must be a positive number, got {arg:?}"),
+    }
 }
 
-impl TryFrom<i64> for Tile {
-    type Error = anyhow::Error;
-
-    fn try_from(value: i64) -> anyhow::Result<Tile> {
-        Ok(match value {
-            0 => Tile::EMPTY,
-            1 => Tile::WALL,
-            2 => Tile::BLOCK,
-            3 => Tile::PADDLE,
-            4 => Tile::BALL,
-            _ => bail!("Bad tile {value}"),
-        })
+fn solve(program: &[i64]) -> anyhow::Result<()> {
+    let mut arcade = Arcade::new(program);
+
+    while arcade.next_update()?.is_some() {}
+
+    println!("Answer 1: {}", arcade.blocks());
+
+    let mut arcade = Arcade::new(program);
+
+    arcade.insert_quarters();
+
+    while let Some(update) = arcade.next_update_with(&mut FollowBall)? {
+        if let Update::Score(score) = update {
+            println!("Score: {score}");
+        }
     }
+
+    println!("Answer 2: {}", arcade.score);
+
+    Ok(())
 }
 
-#[derive(Debug)]
-struct ProgramState {
-    memory: Vec<i64>,
-    func_ptr: usize,
-    input: i64,
-    stop_code: StopCode,
-    output: i64,
-    relative_base: usize,
+/// Plays every strategy to game over and reports how each one did.
+fn benchmark(program: &[i64]) -> anyhow::Result<()> {
+    let strategies: Vec<Box<dyn JoystickStrategy>> =
+        vec![Box::new(FollowBall), Box::new(PredictLanding), Box::new(Random::new(2019))];
+
+    for mut strategy in strategies {
+        let mut arcade = Arcade::new(program);
+        arcade.insert_quarters();
+
+        while arcade.next_update_with(strategy.as_mut())?.is_some() {}
+
+        let cleared = if arcade.blocks() == 0 { "cleared" } else { "not cleared" };
+        println!(
+            "{:>8}: score {:>6}, {:>8} instructions, {} blocks left ({cleared})",
+            strategy.name(),
+            arcade.score,
+            arcade.steps,
+            arcade.blocks()
+        );
+    }
+
+    Ok(())
 }
 
-impl Default for ProgramState {
-    fn default() -> Self {
-        ProgramState {
-            memory: vec![],
-            func_ptr: 0,
-            input: 0,
-            stop_code: StopCode::RUN,
-            output: 0,
-            relative_base: 0,
+/// Plays the game drawing a frame every time the ball moves, at most `fps`
+/// frames a second, redrawn in place. With no `fps` only the final frame is printed.
+fn render(program: &[i64], fps: Option<f64>) -> anyhow::Result<()> {
+    let mut arcade = Arcade::new(program);
+    arcade.insert_quarters();
+
+    if fps.is_some() {
+        print!("\x1b[2J");
+    }
+    let mut next_frame = Instant::now();
+
+    while let Some(update) = arcade.next_update_with(&mut FollowBall)? {
+        if let (Some(fps), Update::Tile(_, Tile::BALL)) = (fps, update) {
+            print!("\x1b[H{}", arcade.render());
+            next_frame += Duration::from_secs_f64(1.0 / fps);
+            std::thread::sleep(next_frame.saturating_duration_since(Instant::now()));
         }
     }
+
+    if fps.is_some() {
+        print!("\x1b[H");
+    }
+    print!("{}", arcade.render());
+
+    Ok(())
 }
 
-type Position = (i64, i64);
+/// Plays the game with the follow-the-ball bot, calling `frame` every time the
+/// ball moves and once more when the game is over.
+fn each_frame(program: &[i64], mut frame: impl FnMut(&Arcade) -> anyhow::Result<()>) -> anyhow::Result<()> {
+    let mut arcade = Arcade::new(program);
+    arcade.insert_quarters();
 
-fn main() -> anyhow::Result<()> {
-    env_logger::init();
+    while let Some(update) = arcade.next_update_with(&mut FollowBall)? {
+        if let Update::Tile(_, Tile::BALL) = update {
+            frame(&arcade)?;
+        }
+    }
 
-    let input = include_str!("../inputs/input-13-2019.txt");
+    frame(&arcade)
+}
 
-    let program: Vec<i64> = input
-        .lines()
-        .filter(|s| !s.is_empty())
-        .flat_map(|l| l.split(',').map(|x| i64::from_str(x).unwrap()))
-        .collect::<Vec<i64>>();
+/// Records a run as an asciicast file playing `fps` frames a second.
+fn record_cast(program: &[i64], path: &Path, fps: f64) -> anyhow::Result<()> {
+    let mut cast = Asciicast::new(0, 0);
 
-    let mut screen: HashMap<Position, Tile> = Default::default();
+    each_frame(program, |arcade| {
+        let text = arcade.render();
+        cast.width = cast.width.max(text.lines().map(|l| l.chars().count()).max().unwrap_or(0));
+        cast.height = cast.height.max(text.lines().count());
+        cast.frame(cast.events.len() as f64 / fps, &text);
+        Ok(())
+    })?;
 
-    let mut state = ProgramState {
-        memory: program.clone(),
-        ..Default::default()
-    };
+    cast.save(path).with_context(|| format!("Writing {}", path.display()))?;
+    println!("Wrote {} frames to {}", cast.events.len(), path.display());
 
-    while state.stop_code == StopCode::RUN {
-        let x = match process(&mut state)? {
-            None => {
-                break;
-            }
-            Some(output) => output,
-        };
-        let y = process(&mut state)?.context("Early exit")?;
-        let tile = Tile::try_from(process(&mut state)?.context("Early exit")?)?;
+    Ok(())
+}
 
-        screen.insert((x, y), tile);
-    }
+/// Records every `every`th frame of a run, plus the last one, as numbered PPM
+/// images with tiles `cell` pixels square.
+fn record_frames(program: &[i64], dir: &Path, every: usize, cell: usize) -> anyhow::Result<()> {
+    let mut frames = FrameDir::create(dir).with_context(|| format!("Creating {}", dir.display()))?;
+    let mut seen = 0;
 
-    println!(
-        "Answer 1: {}",
-        screen.values().filter(|t| t.eq(&&Tile::BLOCK)).count()
-    );
+    each_frame(program, |arcade| {
+        if seen % every == 0 || arcade.state.stop_code == StopCode::TERM {
+            frames.frame(&arcade.render_ppm(cell))?;
+        }
+        seen += 1;
+        Ok(())
+    })?;
 
-    let mut screen: HashMap<Position, Tile> = Default::default();
+    println!("Wrote {} frames to {}", frames.count(), dir.display());
 
-    let mut state = ProgramState {
-        memory: program.clone(),
-        ..Default::default()
-    };
+    Ok(())
+}
 
-    state.memory[0] = 2;
+/// Plays the game to the end and saves the final screen as an image.
+fn screenshot(program: &[i64], path: &Path, cell: usize) -> anyhow::Result<()> {
+    let mut arcade = Arcade::new(program);
+    arcade.insert_quarters();
 
-    let mut score = 0;
+    while arcade.next_update_with(&mut FollowBall)?.is_some() {}
 
-    while state.stop_code == StopCode::RUN {
-        let x = match process(&mut state)? {
-            None => {
-                break;
-            }
-            Some(output) => output,
-        };
-        let y = process(&mut state)?.context("Early exit")?;
-        if x == -1 && y == 0 {
-            score = process(&mut state)?.context("Early Exit")?;
-            println!("Score: {score}");
-            continue;
+    save(&arcade.screen, path, cell, palette)?;
+    println!("Wrote {}", path.display());
+
+    Ok(())
+}
+
+/// Puts the terminal in non-canonical, no-echo mode with non-blocking reads
+/// until dropped.
+struct RawTerminal {
+    saved: String,
+}
+
+impl RawTerminal {
+    fn enable() -> anyhow::Result<RawTerminal> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "min", "0", "time", "0"])?;
+        Ok(RawTerminal { saved: saved.trim().to_owned() })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        if let Err(e) = stty(&[&self.saved]) {
+            eprintln!("Could not restore terminal: {e}");
         }
-        let tile = Tile::try_from(process(&mut state)?.context("Early exit")?)?;
-        screen.insert((x, y), tile);
+    }
+}
 
-        let ball_x = match screen.iter().find(|(_, t)| t.eq(&&Tile::BALL)) {
-            Some((pos, _)) => pos.0,
-            None => {
-                continue;
-            }
-        };
-        let paddle_x = match screen.iter().find(|(_, t)| t.eq(&&Tile::PADDLE)) {
-            Some((pos, _)) => pos.0,
-            None => {
-                continue;
-            }
-        };
-
-        state.input = if ball_x < paddle_x {
-            -1
-        } else if ball_x > paddle_x {
-            1
-        } else {
-            0
-        };
+fn stty(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("stty").args(args).stdin(File::open("/dev/tty")?).output()?;
+    if !output.status.success() {
+        bail!("stty {args:?} failed: {}", String::from_utf8_lossy(&output.stderr));
     }
+    Ok(String::from_utf8(output.stdout)?)
+}
 
-    println!("Answer 2: {score}");
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Key {
+    LEFT,
+    RIGHT,
+    SAVE,
+    LOAD,
+    REWIND,
+    QUIT,
+}
 
-    Ok(())
+fn read_keys() -> anyhow::Result<Vec<Key>> {
+    let mut buf = [0; 64];
+    let len = std::io::stdin().read(&mut buf)?;
+    let mut keys = vec![];
+    let mut bytes = buf[..len].iter();
+    while let Some(byte) = bytes.next() {
+        keys.push(match byte {
+            b'a' => Key::LEFT,
+            b'd' => Key::RIGHT,
+            b's' => Key::SAVE,
+            b'l' => Key::LOAD,
+            b'r' => Key::REWIND,
+            b'q' => Key::QUIT,
+            0x1b if bytes.next() == Some(&b'[') => match bytes.next() {
+                Some(b'D') => Key::LEFT,
+                Some(b'C') => Key::RIGHT,
+                _ => continue,
+            },
+            _ => continue,
+        });
+    }
+    Ok(keys)
 }
 
-fn process(state: &mut ProgramState) -> anyhow::Result<Option<i64>> {
+/// Lets you play the game from the keyboard, advancing `fps` ticks a second.
+/// The last few seconds of frames are kept so `r` can rewind a second at a
+/// time; `s` and `l` save and load a single slot.
+fn play(program: &[i64], fps: f64) -> anyhow::Result<()> {
+    const REWIND_SECONDS: f64 = 10.0;
+    let history_len = ((fps * REWIND_SECONDS) as usize).max(1);
+
+    let mut arcade = Arcade::new(program);
+    arcade.insert_quarters();
+
+    let _terminal = RawTerminal::enable()?;
+    let frame_time = Duration::from_secs_f64(1.0 / fps);
+    let mut history: VecDeque<Arcade> = Default::default();
+    let mut saved: Option<Arcade> = None;
+    let mut joystick = 0;
+
+    print!("\x1b[2J");
+
     loop {
-        let instr = state.memory[state.func_ptr];
-        match instr.rem(100) {
-            1 | 2 | 7 | 8 => {
-                three_param(state)?;
-                state.func_ptr += 4;
-            }
-            3 => {
-                let dest = get_param_dest(state, 1)?;
-                state.memory[dest] = state.input;
-                state.func_ptr += 2;
-            }
-            4 => {
-                let res = get_param_value(state, 1)?;
-                state.func_ptr += 2;
-                state.output = res;
-                return Ok(Some(state.output));
-            }
-            5 => {
-                let param1 = get_param_value(state, 1)?;
-                let param2 = get_param_value(state, 2)?;
-                if param1 != 0 {
-                    state.func_ptr = param2 as usize;
-                } else {
-                    state.func_ptr += 3;
+        history.push_back(arcade.clone());
+        if history.len() > history_len {
+            history.pop_front();
+        }
+        if !arcade.tick(joystick)? {
+            break;
+        }
+
+        print!("\x1b[H{}", arcade.render());
+        println!("←/→ or a/d: move  s: save  l: load  r: rewind  q: quit");
+        std::io::stdout().flush()?;
+        std::thread::sleep(frame_time);
+
+        joystick = 0;
+        for key in read_keys()? {
+            match key {
+                Key::LEFT => joystick = -1,
+                Key::RIGHT => joystick = 1,
+                Key::SAVE => saved = Some(arcade.clone()),
+                Key::LOAD => {
+                    if let Some(state) = &saved {
+                        arcade = state.clone();
+                    }
                 }
-            }
-            6 => {
-                let param1 = get_param_value(state, 1)?;
-                let param2 = get_param_value(state, 2)?;
-                if param1 == 0 {
-                    state.func_ptr = param2 as usize;
-                } else {
-                    state.func_ptr += 3;
+                Key::REWIND => {
+                    let frames = (fps as usize).max(1).min(history.len());
+                    if let Some(state) = history.drain(history.len() - frames..).next() {
+                        arcade = state;
+                    }
                 }
-            }
-            9 => {
-                let param1 = get_param_value(state, 1)?;
-                state.relative_base = if param1.is_negative() {
-                    state.relative_base - param1.wrapping_abs() as usize
-                } else {
-                    state.relative_base + param1 as usize
-                };
-                state.func_ptr += 2;
-            }
-            99 => {
-                state.stop_code = StopCode::TERM;
-                return Ok(None);
-            }
-            _ => {
-                println!("Bad instr {instr} at {0}", state.func_ptr);
-                unreachable!()
+                Key::QUIT => return Ok(()),
             }
         }
     }
+
+    print!("\x1b[H{}", arcade.render());
+    println!("Game over");
+
+    Ok(())
 }
 
-fn three_param(state: &mut ProgramState) -> anyhow::Result<()> {
-    let func_ptr = state.func_ptr;
-    let opcode = state.memory[func_ptr];
-    let _param3_mode = get_mode(opcode, 3)?;
-
-    let func = match opcode.rem(10) {
-        1 => std::ops::Add::add,
-        2 => std::ops::Mul::mul,
-        7 => |x, y| (x < y) as i64,
-        8 => |x, y| (x == y) as i64,
-        x => {
-            println!("Bad opcode {opcode} val {x}");
-            unreachable!()
-        }
-    };
+/// Runs the game for `steps` instructions holding the joystick left, then right,
+/// and prints where the two memories differ.
+fn diff_joystick(program: &[i64], steps: usize) -> anyhow::Result<()> {
+    let kinds = classify(program, &[]);
+
+    let mut machines = [-1, 1].map(|input| ProgramState {
+        memory: program.to_vec(),
+        input,
+        ..Default::default()
+    });
+
+    for machine in &mut machines {
+        machine.memory[0] = 2;
+        run_steps(machine, steps)?;
+    }
 
-    let param1 = get_param_value(state, 1)?;
-    let param2 = get_param_value(state, 2)?;
+    for range in diff(&machines[0].memory, &machines[1].memory, &kinds) {
+        println!("{range}");
+    }
 
-    let dest = get_param_dest(state, 3)?;
-    let res = func(param1, param2);
-    state.memory[dest] = res;
     Ok(())
-    // println!("{opcode} {param1} {param2} wrote {res} to {dest}")
 }
 
-fn get_mode(opcode: i64, pos: usize) -> anyhow::Result<Mode> {
-    match opcode.div(10_i64 * 10_i64.pow(pos as u32)).rem(10) {
-        0 => Ok(Mode::POSITION),
-        1 => Ok(Mode::IMMEDIATE),
-        2 => Ok(Mode::RELATIVE),
-        n => bail!("Unrecognized mode [{n}] in opcode [{opcode}]"),
-    }
+/// Plays the game with the follow-the-ball bot, narrowing down which addresses
+/// hold the ball x, paddle x and number of blocks left as they change on screen.
+fn scan(program: &[i64]) -> anyhow::Result<()> {
+    let found = scan_memory(program)?;
+
+    println!("Ball x: {:?}", found.ball);
+    println!("Paddle x: {:?}", found.paddle);
+    println!("Blocks left: {:?}", found.blocks);
+
+    Ok(())
 }
 
-fn get_param_value(state: &ProgramState, offset: usize) -> anyhow::Result<i64> {
-    let mode = get_mode(state.memory[state.func_ptr], offset)?;
-    match mode {
-        Mode::IMMEDIATE => Ok(access(state, state.func_ptr + offset)),
-        Mode::POSITION => Ok(access(
-            state,
-            access(state, state.func_ptr + offset) as usize,
-        )),
-        Mode::RELATIVE => Ok(access(
-            state,
-            (state.relative_base as i64 + access(state, state.func_ptr + offset)) as usize,
-        )), // _ => { bail!("Unsupported mode {mode:?}") }
-    }
+/// Memory addresses that could hold each tracked value.
+struct Found {
+    ball: Vec<usize>,
+    paddle: Vec<usize>,
+    blocks: Vec<usize>,
 }
 
-fn get_param_dest(state: &mut ProgramState, offset: usize) -> anyhow::Result<usize> {
-    let mode = get_mode(state.memory[state.func_ptr], offset)?;
-    let result = match mode {
-        Mode::POSITION => access(state, state.func_ptr + offset) as usize,
-        Mode::RELATIVE => {
-            (state.relative_base as i64 + access(state, state.func_ptr + offset)) as usize
+fn scan_memory(program: &[i64]) -> anyhow::Result<Found> {
+    let mut arcade = Arcade::new(program);
+    arcade.insert_quarters();
+
+    let mut ball = Scanner::new(&arcade.state.memory);
+    let mut paddle = Scanner::new(&arcade.state.memory);
+    let mut blocks = Scanner::new(&arcade.state.memory);
+    let mut blocks_left = 0;
+    // The game lowers its counter only after erasing the block and updating
+    // the score, so the count is checked when the ball is next drawn.
+    let mut broke = false;
+
+    while let Some(update) = arcade.next_update_with(&mut FollowBall)? {
+        match update {
+            Update::Tile(Point2 { x, .. }, Tile::BALL) => {
+                ball.scan(&arcade.state.memory, Predicate::Equals(x));
+                if std::mem::take(&mut broke) {
+                    blocks.scan(&arcade.state.memory, Predicate::Equals(blocks_left as i64));
+                }
+            }
+            Update::Tile(Point2 { x, .. }, Tile::PADDLE) => {
+                paddle.scan(&arcade.state.memory, Predicate::Equals(x));
+            }
+            Update::Tile(_, Tile::EMPTY) => {
+                let left = arcade.blocks();
+                broke |= left < blocks_left;
+                blocks_left = left;
+            }
+            _ => {}
         }
-        Mode::IMMEDIATE => bail!("Can not write in Immediate mode"),
-    };
-
-    if state.memory.len() <= result {
-        state.memory.resize(result + 1, 0)
     }
-    Ok(result)
+
+    Ok(Found {
+        ball: ball.candidates().to_vec(),
+        paddle: paddle.candidates().to_vec(),
+        blocks: blocks.candidates().to_vec(),
+    })
 }
 
-fn access(state: &ProgramState, addr: usize) -> i64 {
-    match state.memory.get(addr) {
-        None => 0,
-        Some(x) => *x,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_finds_game_state() {
+        let program: Vec<i64> = load(Source::Str(include_str!("../inputs/input-13-2019.txt"))).unwrap();
+        let found = scan_memory(&program).unwrap();
+        assert_eq!(found.ball, [388]);
+        assert_eq!(found.paddle, [392]);
+        assert_eq!(found.blocks, [387]);
     }
 }
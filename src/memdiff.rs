@@ -0,0 +1,53 @@
+use std::fmt::{Display, Formatter};
+
+use crate::disasm::CellKind;
+
+/// Contiguous run of changed cells that are all code or all data.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DiffRange {
+    pub start: usize,
+    pub kind: CellKind,
+    pub old: Vec<i64>,
+    pub new: Vec<i64>,
+}
+
+impl DiffRange {
+    /// Last address in the range.
+    pub fn end(&self) -> usize {
+        self.start + self.old.len() - 1
+    }
+}
+
+impl Display for DiffRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            CellKind::CODE => "code",
+            CellKind::DATA => "data",
+        };
+        write!(f, "{:>5}..={:<5} {kind}: {:?} -> {:?}", self.start, self.end(), self.old, self.new)
+    }
+}
+
+/// Lists the cells that differ between `old` and `new`. Cells past the end of
+/// either memory read as 0, and cells past the end of `kinds` count as data.
+pub fn diff(old: &[i64], new: &[i64], kinds: &[CellKind]) -> Vec<DiffRange> {
+    let mut ranges: Vec<DiffRange> = vec![];
+
+    for addr in 0..old.len().max(new.len()) {
+        let before = old.get(addr).copied().unwrap_or(0);
+        let after = new.get(addr).copied().unwrap_or(0);
+        if before == after {
+            continue;
+        }
+        let kind = kinds.get(addr).copied().unwrap_or(CellKind::DATA);
+        match ranges.last_mut() {
+            Some(range) if range.end() + 1 == addr && range.kind == kind => {
+                range.old.push(before);
+                range.new.push(after);
+            }
+            _ => ranges.push(DiffRange { start: addr, kind, old: vec![before], new: vec![after] }),
+        }
+    }
+
+    ranges
+}
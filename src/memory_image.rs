@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+
+use crate::intcode::ProgramState;
+use crate::loader::{load, LoadError, Source};
+
+/// Layout: `MAGIC`, a version byte, the cell count as a varint, then the cells.
+/// Each non-zero cell is a zigzag varint; a run of zeros is a `0` byte followed
+/// by the run length, at least 1, as a varint.
+pub const MAGIC: &[u8; 4] = b"ICMI";
+pub const VERSION: u8 = 1;
+/// Most cells an image may declare, so a bad header can't ask for gigabytes.
+pub const MAX_CELLS: u64 = 1 << 24;
+
+#[derive(Debug, Snafu)]
+pub enum ImageError {
+    #[snafu(display("Not a memory image, expected magic {MAGIC:?}"))]
+    BadMagic,
+    #[snafu(display("Unsupported memory image version {version}"))]
+    UnsupportedVersion { version: u8 },
+    #[snafu(display("Memory image truncated at byte {offset}"))]
+    Truncated { offset: usize },
+    #[snafu(display("Varint too long at byte {offset}"))]
+    Overlong { offset: usize },
+    #[snafu(display("Zero run of length 0 at byte {offset}"))]
+    EmptyRun { offset: usize },
+    #[snafu(display("Memory image declares {cells} cells, more than the {MAX_CELLS} allowed"))]
+    TooLarge { cells: u64 },
+    #[snafu(display("Memory image holds {found} cells but its header says {expected}"))]
+    LengthMismatch { expected: u64, found: u64 },
+    #[snafu(display("Could not access {name}: {source}"))]
+    Io { name: String, source: std::io::Error },
+    #[snafu(display("Bad text program: {source}"))]
+    Text { source: LoadError },
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn read_varint(bytes: &[u8], offset: &mut usize) -> Result<u64, ImageError> {
+    let start = *offset;
+    let mut res = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*offset).context(TruncatedSnafu { offset: *offset })?;
+        *offset += 1;
+        // Only the lowest bit of a tenth byte fits in a u64, and it must be the last.
+        ensure!(shift < 63 || byte <= 1, OverlongSnafu { offset: start });
+        res |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(res);
+        }
+    }
+    OverlongSnafu { offset: start }.fail()
+}
+
+pub fn encode(memory: &[i64]) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.push(VERSION);
+    write_varint(&mut out, memory.len() as u64);
+
+    let mut cells = memory.iter().peekable();
+    while let Some(cell) = cells.next() {
+        if *cell == 0 {
+            let mut run = 1;
+            while cells.next_if_eq(&&0).is_some() {
+                run += 1;
+            }
+            out.push(0);
+            write_varint(&mut out, run);
+        } else {
+            write_varint(&mut out, zigzag(*cell));
+        }
+    }
+    out
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<i64>, ImageError> {
+    ensure!(bytes.starts_with(MAGIC), BadMagicSnafu);
+    let version = *bytes.get(MAGIC.len()).context(TruncatedSnafu { offset: MAGIC.len() })?;
+    ensure!(version == VERSION, UnsupportedVersionSnafu { version });
+
+    let mut offset = MAGIC.len() + 1;
+    let expected = read_varint(bytes, &mut offset)?;
+    ensure!(expected <= MAX_CELLS, TooLargeSnafu { cells: expected });
+    let mut memory = vec![];
+
+    while offset < bytes.len() {
+        let cell = read_varint(bytes, &mut offset)?;
+        let count = match cell {
+            0 => {
+                let run = offset;
+                let count = read_varint(bytes, &mut offset)?;
+                ensure!(count > 0, EmptyRunSnafu { offset: run });
+                count
+            }
+            _ => 1,
+        };
+        // Saturates rather than wrapping, so an overflowing run still fails the check.
+        let found = (memory.len() as u64).saturating_add(count);
+        ensure!(found <= expected, LengthMismatchSnafu { expected, found });
+        match cell {
+            0 => memory.resize(found as usize, 0),
+            n => memory.push(unzigzag(n)),
+        }
+    }
+
+    ensure!(memory.len() as u64 == expected, LengthMismatchSnafu { expected, found: memory.len() as u64 });
+    Ok(memory)
+}
+
+pub fn save(state: &ProgramState, path: &Path) -> Result<(), ImageError> {
+    std::fs::write(path, encode(&state.memory)).context(IoSnafu { name: path.display().to_string() })
+}
+
+/// Loads a memory image into a fresh `ProgramState`.
+pub fn load_state(path: &Path) -> Result<ProgramState, ImageError> {
+    let bytes = std::fs::read(path).context(IoSnafu { name: path.display().to_string() })?;
+    Ok(ProgramState {
+        memory: decode(&bytes)?,
+        ..Default::default()
+    })
+}
+
+pub fn text_to_binary(text: &str) -> Result<Vec<u8>, ImageError> {
+    let memory: Vec<i64> = load(Source::Str(text)).context(TextSnafu)?;
+    Ok(encode(&memory))
+}
+
+pub fn binary_to_text(bytes: &[u8]) -> Result<String, ImageError> {
+    let memory = decode(bytes)?;
+    Ok(memory.iter().map(i64::to_string).collect::<Vec<String>>().join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(text: &str) {
+        let bytes = text_to_binary(text).unwrap();
+        assert_eq!(binary_to_text(&bytes).unwrap(), text);
+    }
+
+    #[test]
+    fn round_trips_text() {
+        round_trip("1,9,10,3,2,3,11,0,99,30,40,50");
+        round_trip(&format!("{},{},-1,0,{}", i64::MIN, i64::MAX, i64::MIN + 1));
+        round_trip("-5,-123456789,7,-1");
+        round_trip(&format!("3,{}4,99", "0,".repeat(100_000)));
+        round_trip("0");
+    }
+
+    #[test]
+    fn round_trips_empty_memory() {
+        let bytes = encode(&[]);
+        assert_eq!(decode(&bytes).unwrap(), Vec::<i64>::new());
+        assert_eq!(binary_to_text(&bytes).unwrap(), "");
+    }
+
+    #[test]
+    fn compresses_zero_runs() {
+        let memory = vec![0; 10_000];
+        assert_eq!(encode(&memory).len(), 10);
+        assert_eq!(decode(&encode(&memory)).unwrap(), memory);
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        let mut bytes = encode(&[1, 2, 3]);
+        assert!(matches!(decode(b"ICMX\x01\x00"), Err(ImageError::BadMagic)));
+        assert!(matches!(decode(b"IC"), Err(ImageError::BadMagic)));
+        assert!(matches!(decode(MAGIC), Err(ImageError::Truncated { offset: 4 })));
+        bytes[4] = 2;
+        assert!(matches!(decode(&bytes), Err(ImageError::UnsupportedVersion { version: 2 })));
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let bytes = encode(&[1, 300, 0, 0, 5]);
+        for len in 5..bytes.len() {
+            assert!(decode(&bytes[..len]).is_err(), "accepted {len} of {} bytes", bytes.len());
+        }
+        let mut overlong = MAGIC.to_vec();
+        overlong.push(VERSION);
+        overlong.extend([0x80; 11]);
+        assert!(matches!(decode(&overlong), Err(ImageError::Overlong { offset: 5 })));
+
+        // Ten bytes can only carry one more bit once 63 have been read.
+        for last in [0x02, 0x7f] {
+            let mut header = MAGIC.to_vec();
+            header.push(VERSION);
+            header.extend([0xff; 9]);
+            header.push(last);
+            assert!(matches!(decode(&header), Err(ImageError::Overlong { offset: 5 })), "accepted {last:#x}");
+        }
+        let mut max = vec![];
+        write_varint(&mut max, u64::MAX);
+        assert_eq!(read_varint(&max, &mut 0).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn rejects_empty_zero_runs() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        write_varint(&mut bytes, 1);
+        bytes.extend([0, 0, 2]);
+        assert!(matches!(decode(&bytes), Err(ImageError::EmptyRun { offset: 7 })));
+    }
+
+    #[test]
+    fn rejects_oversized_images() {
+        let mut huge = MAGIC.to_vec();
+        huge.push(VERSION);
+        write_varint(&mut huge, u64::MAX);
+        assert!(matches!(decode(&huge), Err(ImageError::TooLarge { cells: u64::MAX })));
+
+        // A zero run longer than the declared length, and one that would overflow.
+        for run in [11, u64::MAX] {
+            let mut bytes = MAGIC.to_vec();
+            bytes.push(VERSION);
+            write_varint(&mut bytes, 10);
+            bytes.push(2);
+            bytes.push(0);
+            write_varint(&mut bytes, run);
+            assert!(matches!(decode(&bytes), Err(ImageError::LengthMismatch { expected: 10, .. })));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt::{Display, Formatter};
+
+use crate::geom::Point2;
+use crate::grid::Grid;
+
+const GLYPH_WIDTH: i64 = 4;
+const GLYPH_HEIGHT: i64 = 6;
+/// Distance from the start of one glyph to the next, leaving a blank column.
+const PITCH: i64 = GLYPH_WIDTH + 1;
+
+/// The block-letter font the puzzles draw their answers in.
+const FONT: [(char, [&str; 6]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...", "#...", ".#.#", "..#.", "..#.", "..#."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Text read from a grid. Glyphs that didn't match any letter are `?` in
+/// `text` and listed by index in `unrecognised`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Reading {
+    pub text: String,
+    pub unrecognised: Vec<usize>,
+}
+
+impl Reading {
+    pub fn is_complete(&self) -> bool {
+        self.unrecognised.is_empty()
+    }
+}
+
+impl Display for Reading {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)?;
+        if !self.is_complete() {
+            write!(f, " (unrecognised glyphs at {:?})", self.unrecognised)?;
+        }
+        Ok(())
+    }
+}
+
+/// Pixels of a glyph packed into bits, row by row from the top left.
+fn pack(lit: impl Fn(i64, i64) -> bool) -> u32 {
+    let mut bits = 0;
+    for y in 0..GLYPH_HEIGHT {
+        for x in 0..GLYPH_WIDTH {
+            bits = bits << 1 | lit(x, y) as u32;
+        }
+    }
+    bits
+}
+
+fn letter(bits: u32) -> Option<char> {
+    FONT.iter()
+        .find(|(_, rows)| pack(|x, y| rows[y as usize].as_bytes()[x as usize] == b'#') == bits)
+        .map(|(c, _)| *c)
+}
+
+/// Reads the letters drawn by the cells `lit` is true for. Letters are found
+/// from the top left lit pixel, every five columns.
+pub fn read<T>(grid: &Grid<T>, lit: impl Fn(&T) -> bool) -> Reading {
+    let pixels = grid.iter().filter(|(_, value)| lit(value)).map(|(pos, _)| pos).collect::<Vec<_>>();
+    let (Some(left), Some(right), Some(top)) = (
+        pixels.iter().map(|p| p.x).min(),
+        pixels.iter().map(|p| p.x).max(),
+        pixels.iter().map(|p| p.y).min(),
+    ) else {
+        return Reading { text: String::new(), unrecognised: vec![] };
+    };
+    let is_lit = |x, y| grid.get(Point2::new(x, y)).is_some_and(&lit);
+
+    // Letters like I don't light their first column, so try starting one
+    // column early as well and keep whichever reads better.
+    (0..=1)
+        .map(|shift| {
+            let start = left - shift;
+            let glyphs = (right - start) / PITCH + 1;
+            let mut reading = Reading { text: String::new(), unrecognised: vec![] };
+            for i in 0..glyphs {
+                let origin = start + i * PITCH;
+                match letter(pack(|x, y| is_lit(origin + x, top + y))) {
+                    Some(c) => reading.text.push(c),
+                    None => {
+                        reading.text.push('?');
+                        reading.unrecognised.push(i as usize);
+                    }
+                }
+            }
+            reading
+        })
+        .min_by_key(|reading| reading.unrecognised.len())
+        .expect("two alignments tried")
+}
+
+/// Reads letters from text as printed by the days, with `█` or `#` for lit pixels.
+pub fn read_text(rendered: &str) -> Reading {
+    read(&Grid::parse(rendered, |_, c| matches!(c, '█' | '#').then_some(())), |_| true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::{load, Source};
+    use crate::robot::{Color, Robot};
+    use crate::space_image::{Pixel, SpaceImage};
+
+    /// The rows of `glyphs` side by side with a blank column after each.
+    fn render(glyphs: &[[&str; 6]]) -> String {
+        (0..6).map(|y| glyphs.iter().map(|rows| format!("{}.", rows[y])).collect::<String>() + "\n").collect()
+    }
+
+    fn glyph(c: char) -> [&'static str; 6] {
+        FONT.iter().find(|(letter, _)| *letter == c).unwrap().1
+    }
+
+    #[test]
+    fn reads_known_rendering() {
+        let reading = read_text(&render(&[glyph('H'), glyph('E'), glyph('L'), glyph('P')]));
+        assert_eq!(reading.text, "HELP");
+        assert!(reading.is_complete());
+        assert_eq!(reading.to_string(), "HELP");
+    }
+
+    #[test]
+    fn marks_unknown_glyphs() {
+        let blob = ["####", "####", "####", "####", "####", "####"];
+        let reading = read_text(&render(&[glyph('A'), blob, glyph('B')]));
+        assert_eq!(reading.text, "A?B");
+        assert_eq!(reading.unrecognised, [1]);
+        assert_eq!(reading.to_string(), "A?B (unrecognised glyphs at [1])");
+    }
+
+    #[test]
+    fn shifts_for_unlit_first_column() {
+        assert_eq!(read_text(&render(&[glyph('I'), glyph('Z')])).text, "IZ");
+        assert_eq!(read_text(&render(&[glyph('J'), glyph('I')])).text, "JI");
+    }
+
+    #[test]
+    fn reads_nothing_from_empty_grid() {
+        for rendered in ["", "....\n....\n"] {
+            let reading = read_text(rendered);
+            assert_eq!(reading.text, "");
+            assert!(reading.is_complete());
+        }
+    }
+
+    #[test]
+    fn reads_day_8_and_day_11() {
+        let image = SpaceImage::decode_padded(include_str!("../inputs/input-08-2019.txt"), 25, 6).unwrap();
+        assert_eq!(read(&image.composite(), |pixel| *pixel == Pixel::WHITE).to_string(), "EHRUE");
+
+        let program: Vec<i64> = load(Source::Str(include_str!("../inputs/input-11-2019.txt"))).unwrap();
+        let mut robot = Robot::new(&program, Color::WHITE);
+        robot.run().unwrap();
+        assert_eq!(read(&robot.map, |color| matches!(color, Color::WHITE)).to_string(), "JHARBGCU");
+    }
+}
@@ -0,0 +1,401 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use snafu::Snafu;
+
+/// The object everything orbits, directly or not.
+pub const ROOT: &str = "COM";
+
+#[derive(Debug, Snafu)]
+pub enum OrbitError {
+    #[snafu(display("Line {line} is not of the form A)B: {text:?}"))]
+    Malformed { line: usize, text: String },
+    #[snafu(display("{object} orbits both {first} and {second}"))]
+    Duplicate { object: String, first: String, second: String },
+    #[snafu(display("Orbits form a cycle: {}", objects.join(" -> ")))]
+    Cycle { objects: Vec<String> },
+    #[snafu(display("{object} orbits nothing, so {count} objects don't lead back to {ROOT}"))]
+    Orphan { object: String, count: usize },
+    #[snafu(display("No object orbits {ROOT}"))]
+    MissingRoot,
+}
+
+/// Which object orbits which, checked to form a single tree around [`ROOT`].
+#[derive(Debug, Clone)]
+pub struct OrbitMap {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    depths: Vec<usize>,
+}
+
+impl OrbitMap {
+    /// Reads one `CENTER)SATELLITE` pair per line, skipping blank lines and
+    /// repeated pairs.
+    pub fn parse(input: &str) -> Result<Self, OrbitError> {
+        let mut map = OrbitMap {
+            names: vec![],
+            index: HashMap::new(),
+            parents: vec![],
+            children: vec![],
+            depths: vec![],
+        };
+
+        for (line, text) in input.lines().enumerate() {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let malformed = || OrbitError::Malformed { line: line + 1, text: text.to_owned() };
+            let (center, satellite) = text.trim().split_once(')').ok_or_else(malformed)?;
+            if center.is_empty() || satellite.is_empty() || satellite.contains(')') || center == satellite {
+                return Err(malformed());
+            }
+            let (center, satellite) = (map.intern(center), map.intern(satellite));
+            if map.parents[satellite] == Some(center) {
+                continue;
+            }
+            if let Some(first) = map.parents[satellite] {
+                return DuplicateSnafu {
+                    object: &map.names[satellite],
+                    first: &map.names[first],
+                    second: &map.names[center],
+                }
+                .fail();
+            }
+            map.parents[satellite] = Some(center);
+            map.children[center].push(satellite);
+        }
+
+        map.compute_depths()?;
+        Ok(map)
+    }
+
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(id) = self.index.get(name) {
+            return *id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_owned());
+        self.index.insert(name.to_owned(), id);
+        self.parents.push(None);
+        self.children.push(vec![]);
+        id
+    }
+
+    /// Sets every depth from the root down, then explains whatever the root
+    /// didn't reach, or how the root came to orbit something.
+    fn compute_depths(&mut self) -> Result<(), OrbitError> {
+        let root = *self.index.get(ROOT).ok_or(OrbitError::MissingRoot)?;
+        let mut depths = vec![None; self.names.len()];
+        depths[root] = Some(0);
+        let mut queue = VecDeque::from([root]);
+        while let Some(id) = queue.pop_front() {
+            for child in &self.children[id] {
+                if depths[*child].is_none() {
+                    depths[*child] = depths[id].map(|d| d + 1);
+                    queue.push_back(*child);
+                }
+            }
+        }
+
+        let unreached = (0..self.names.len()).filter(|id| depths[*id].is_none()).collect::<Vec<usize>>();
+        if let Some(start) = unreached.iter().copied().min_by_key(|id| &self.names[*id]) {
+            return Err(self.broken_chain(start, unreached.len()));
+        }
+        if self.parents[root].is_some() {
+            return Err(self.broken_chain(root, 0));
+        }
+
+        self.depths = depths.into_iter().map(|d| d.unwrap_or(0)).collect();
+        Ok(())
+    }
+
+    /// Follows what `start` orbits until an object repeats, giving the
+    /// cycle, or until one orbits nothing, giving the orphan.
+    fn broken_chain(&self, start: usize, unreached: usize) -> OrbitError {
+        let mut path = vec![start];
+        let mut seen = HashSet::from([start]);
+        let mut id = start;
+        while let Some(parent) = self.parents[id] {
+            if !seen.insert(parent) {
+                let from = path.iter().position(|p| *p == parent).unwrap_or(0);
+                let mut objects = path[from..].iter().map(|p| self.names[*p].clone()).collect::<Vec<String>>();
+                objects.push(self.names[parent].clone());
+                return OrbitError::Cycle { objects };
+            }
+            path.push(parent);
+            id = parent;
+        }
+        OrbitError::Orphan {
+            object: self.names[id].clone(),
+            count: unreached,
+        }
+    }
+
+    fn id(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    /// Number of objects, the root included.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// Every object, in the order they first appear in the input.
+    pub fn objects(&self) -> impl Iterator<Item = &str> + '_ {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// Object `name` directly orbits, or `None` for the root and unknown objects.
+    pub fn parent(&self, name: &str) -> Option<&str> {
+        Some(&self.names[self.parents[self.id(name)?]?])
+    }
+
+    /// Objects directly orbiting `name`.
+    pub fn satellites(&self, name: &str) -> impl Iterator<Item = &str> + '_ {
+        let ids = self.id(name).map(|id| self.children[id].as_slice()).unwrap_or_default();
+        ids.iter().map(|id| self.names[*id].as_str())
+    }
+
+    /// Number of direct and indirect orbits of `name`, its distance from the root.
+    pub fn depth(&self, name: &str) -> Option<usize> {
+        Some(self.depths[self.id(name)?])
+    }
+
+    /// Direct and indirect orbits of every object.
+    pub fn total_orbits(&self) -> usize {
+        self.depths.iter().sum()
+    }
+
+    /// Objects `name` orbits, directly first, up to and including the root.
+    pub fn ancestors(&self, name: &str) -> impl Iterator<Item = &str> + '_ {
+        let mut id = self.id(name);
+        std::iter::from_fn(move || {
+            id = self.parents[id?];
+            Some(self.names[id?].as_str())
+        })
+    }
+
+    /// Deepest object both `a` and `b` are or orbit.
+    pub fn lowest_common_ancestor(&self, a: &str, b: &str) -> Option<&str> {
+        let (mut a, mut b) = (self.id(a)?, self.id(b)?);
+        while self.depths[a] > self.depths[b] {
+            a = self.parents[a]?;
+        }
+        while self.depths[b] > self.depths[a] {
+            b = self.parents[b]?;
+        }
+        while a != b {
+            a = self.parents[a]?;
+            b = self.parents[b]?;
+        }
+        Some(&self.names[a])
+    }
+
+    /// Moves between objects in the tree to get from `a` to `b`.
+    pub fn distance(&self, a: &str, b: &str) -> Option<usize> {
+        let common = self.depth(self.lowest_common_ancestor(a, b)?)?;
+        Some(self.depth(a)? + self.depth(b)? - 2 * common)
+    }
+
+    /// Orbital transfers to get from the object `a` orbits to the one `b` orbits.
+    pub fn transfers(&self, a: &str, b: &str) -> Option<usize> {
+        self.distance(self.parent(a)?, self.parent(b)?)
+    }
+
+    /// Objects passed going from `a` to `b` through the tree, both included.
+    pub fn path(&self, a: &str, b: &str) -> Option<Vec<&str>> {
+        let common = self.lowest_common_ancestor(a, b)?;
+        let up = std::iter::once(self.names[self.id(a)?].as_str()).chain(self.ancestors(a));
+        let mut path = up.take_while(|name| *name != common).collect::<Vec<&str>>();
+        path.push(common);
+        let down = std::iter::once(self.names[self.id(b)?].as_str()).chain(self.ancestors(b));
+        let mut down = down.take_while(|name| *name != common).collect::<Vec<&str>>();
+        down.reverse();
+        path.extend(down);
+        Some(path)
+    }
+
+    pub fn stats(&self) -> OrbitStats {
+        let deepest = (0..self.len()).max_by_key(|id| (self.depths[*id], Reverse(&self.names[*id])));
+        let centers = self.children.iter().filter(|c| !c.is_empty()).count();
+        OrbitStats {
+            objects: self.len(),
+            direct: self.parents.iter().flatten().count(),
+            total: self.total_orbits(),
+            max_depth: deepest.map(|id| self.depths[id]).unwrap_or(0),
+            deepest: deepest.map(|id| self.names[id].clone()).unwrap_or_default(),
+            leaves: self.len() - centers,
+            mean_branching: self.parents.iter().flatten().count() as f64 / centers.max(1) as f64,
+            max_branching: self.children.iter().map(Vec::len).max().unwrap_or(0),
+        }
+    }
+
+    /// Ids of the objects orbiting `id`, sorted by name.
+    fn sorted_children(&self, id: usize) -> Vec<usize> {
+        let mut children = self.children[id].clone();
+        children.sort_unstable_by_key(|c| &self.names[*c]);
+        children
+    }
+
+    /// Graphviz digraph with an edge from each object to its satellites.
+    /// Objects in `highlight` and the edges between them are drawn in red.
+    pub fn dot(&self, highlight: &[&str]) -> String {
+        let marked = |id: usize| highlight.contains(&self.names[id].as_str());
+        let mut out = "digraph orbits {\n    rankdir=LR;\n    node [shape=circle];\n".to_owned();
+        for id in 0..self.len() {
+            if marked(id) {
+                out.push_str(&format!("    \"{}\" [color=red, fontcolor=red];\n", self.names[id]));
+            }
+        }
+        for id in 0..self.len() {
+            for child in self.sorted_children(id) {
+                let style = if marked(id) && marked(child) { " [color=red, penwidth=2]" } else { "" };
+                out.push_str(&format!("    \"{}\" -> \"{}\"{style};\n", self.names[id], self.names[child]));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// One object per line, indented by depth under what it orbits, with
+    /// satellites sorted by name. Objects in `highlight` are starred.
+    pub fn tree(&self, highlight: &[&str]) -> String {
+        let mut out = String::new();
+        let mut stack = self.id(ROOT).into_iter().collect::<Vec<usize>>();
+        while let Some(id) = stack.pop() {
+            let name = &self.names[id];
+            let mark = if highlight.contains(&name.as_str()) { " *" } else { "" };
+            out.push_str(&format!("{}{name}{mark}\n", "  ".repeat(self.depths[id])));
+            stack.extend(self.sorted_children(id).into_iter().rev());
+        }
+        out
+    }
+}
+
+/// Shape of an orbit map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitStats {
+    pub objects: usize,
+    pub direct: usize,
+    /// Direct and indirect orbits.
+    pub total: usize,
+    pub max_depth: usize,
+    /// First by name of the objects at `max_depth`.
+    pub deepest: String,
+    /// Objects nothing orbits.
+    pub leaves: usize,
+    /// Satellites per object that has any.
+    pub mean_branching: f64,
+    pub max_branching: usize,
+}
+
+impl Display for OrbitStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Objects:         {}", self.objects)?;
+        writeln!(f, "Direct orbits:   {}", self.direct)?;
+        writeln!(f, "Indirect orbits: {}", self.total - self.direct)?;
+        writeln!(f, "Total orbits:    {}", self.total)?;
+        writeln!(f, "Max depth:       {} ({})", self.max_depth, self.deepest)?;
+        writeln!(f, "Leaves:          {}", self.leaves)?;
+        writeln!(f, "Branching:       {:.3} mean, {} max", self.mean_branching, self.max_branching)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
+
+    fn with_you_and_san() -> OrbitMap {
+        OrbitMap::parse(&format!("{EXAMPLE}\nK)YOU\nI)SAN")).unwrap()
+    }
+
+    #[test]
+    fn counts_example_orbits() {
+        let map = OrbitMap::parse(EXAMPLE).unwrap();
+        assert_eq!(map.len(), 12);
+        assert_eq!(map.total_orbits(), 42);
+        assert_eq!(map.depth("L"), Some(7));
+        assert_eq!(map.depth("COM"), Some(0));
+        assert_eq!(map.parent("COM"), None);
+    }
+
+    #[test]
+    fn counts_example_transfers() {
+        let map = with_you_and_san();
+        assert_eq!(map.transfers("YOU", "SAN"), Some(4));
+        assert_eq!(map.transfers("YOU", "COM"), None);
+        assert_eq!(map.transfers("YOU", "NOPE"), None);
+    }
+
+    #[test]
+    fn finds_lowest_common_ancestor() {
+        let map = with_you_and_san();
+        assert_eq!(map.lowest_common_ancestor("YOU", "SAN"), Some("D"));
+        assert_eq!(map.lowest_common_ancestor("L", "H"), Some("B"));
+        assert_eq!(map.lowest_common_ancestor("K", "YOU"), Some("K"));
+        assert_eq!(map.lowest_common_ancestor("COM", "F"), Some("COM"));
+    }
+
+    #[test]
+    fn finds_paths() {
+        let map = with_you_and_san();
+        assert_eq!(map.path("YOU", "SAN").unwrap(), ["YOU", "K", "J", "E", "D", "I", "SAN"]);
+        assert_eq!(map.path("COM", "C").unwrap(), ["COM", "B", "C"]);
+        assert_eq!(map.path("F", "F").unwrap(), ["F"]);
+        assert_eq!(map.distance("H", "L"), Some(8));
+    }
+
+    #[test]
+    fn rejects_duplicates() {
+        let err = OrbitMap::parse("COM)A\nA)B\nCOM)B").unwrap_err();
+        assert!(matches!(err, OrbitError::Duplicate { .. }));
+        assert_eq!(err.to_string(), "B orbits both A and COM");
+
+        let map = OrbitMap::parse("COM)A\nA)B\nA)B").unwrap();
+        assert_eq!(map.satellites("A").collect::<Vec<&str>>(), ["B"]);
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let err = OrbitMap::parse("COM)A\nX)Y\nY)X").unwrap_err();
+        assert!(matches!(&err, OrbitError::Cycle { objects } if objects == &["X", "Y", "X"]));
+
+        let err = OrbitMap::parse("COM)A\nA)COM").unwrap_err();
+        assert!(matches!(&err, OrbitError::Cycle { objects } if objects == &["COM", "A", "COM"]));
+    }
+
+    #[test]
+    fn rejects_orphans() {
+        let err = OrbitMap::parse("COM)A\nX)Y\nY)Z").unwrap_err();
+        assert!(matches!(&err, OrbitError::Orphan { object, count: 3 } if object == "X"));
+
+        let err = OrbitMap::parse("COM)A\nX)COM").unwrap_err();
+        assert!(matches!(&err, OrbitError::Orphan { object, .. } if object == "X"));
+    }
+
+    #[test]
+    fn rejects_missing_root() {
+        assert!(matches!(OrbitMap::parse("A)B\nB)C"), Err(OrbitError::MissingRoot)));
+        assert!(matches!(OrbitMap::parse(""), Err(OrbitError::MissingRoot)));
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for bad in ["COM)A\nAB", "COM)A\n)B", "COM)A\nA)", "COM)A\nA)B)C", "COM)A\nA)A"] {
+            assert!(matches!(OrbitMap::parse(bad), Err(OrbitError::Malformed { line: 2, .. })), "{bad:?}");
+        }
+    }
+}
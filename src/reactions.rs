@@ -0,0 +1,405 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+
+use nom::bytes::complete::tag;
+use nom::character::complete::{alpha1, char, space0, u64 as number};
+use nom::combinator::{all_consuming, map};
+use nom::multi::separated_list1;
+use nom::sequence::{delimited, separated_pair};
+use nom::IResult;
+use snafu::Snafu;
+
+pub const ORE: &str = "ORE";
+pub const FUEL: &str = "FUEL";
+
+#[derive(Debug, Snafu)]
+pub enum ReactionError {
+    #[snafu(display("Could not parse line {line}: {text:?}"))]
+    Parse { line: usize, text: String },
+    #[snafu(display("{chemical} is produced by more than one reaction"))]
+    DuplicateOutput { chemical: String },
+    #[snafu(display("Nothing produces {chemical}"))]
+    Unproducible { chemical: String },
+    #[snafu(display("Reactions form a cycle through {}", chemicals.join(" -> ")))]
+    Cycle { chemicals: Vec<String> },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Ingredient {
+    pub quantity: u64,
+    pub chemical: String,
+}
+
+impl Display for Ingredient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.quantity, self.chemical)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Reaction {
+    pub inputs: Vec<Ingredient>,
+    pub output: Ingredient,
+}
+
+impl Display for Reaction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let inputs = self.inputs.iter().map(Ingredient::to_string).collect::<Vec<String>>();
+        write!(f, "{} => {}", inputs.join(", "), self.output)
+    }
+}
+
+fn ingredient(input: &str) -> IResult<&str, Ingredient> {
+    map(separated_pair(number, char(' '), alpha1), |(quantity, chemical): (u64, &str)| Ingredient {
+        quantity,
+        chemical: chemical.to_owned(),
+    })(input)
+}
+
+fn reaction(input: &str) -> IResult<&str, Reaction> {
+    map(
+        separated_pair(separated_list1(tag(", "), ingredient), tag(" => "), ingredient),
+        |(inputs, output)| Reaction { inputs, output },
+    )(input)
+}
+
+/// Parses one reaction per line, such as `3 NPNGZ, 3 TBFQ, 1 RZBF => 2 LQNR`.
+/// Blank lines are skipped.
+pub fn parse(input: &str) -> Result<Vec<Reaction>, ReactionError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .map(|(index, text)| {
+            all_consuming(delimited(space0, reaction, space0))(text)
+                .map(|(_, reaction)| reaction)
+                .map_err(|_| ReactionError::Parse {
+                    line: index + 1,
+                    text: text.to_owned(),
+                })
+        })
+        .collect()
+}
+
+/// How much of each chemical a run of the factory made and used up. The
+/// chemical asked for counts as consumed by whoever asked for it.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Production {
+    pub produced: BTreeMap<String, u64>,
+    pub consumed: BTreeMap<String, u64>,
+}
+
+impl Production {
+    /// ORE taken from the cargo hold, which nothing produces.
+    pub fn ore(&self) -> u64 {
+        self.consumed.get(ORE).copied().unwrap_or(0)
+    }
+
+    /// Amount of `chemical` made but never used.
+    pub fn leftover(&self, chemical: &str) -> u64 {
+        let produced = self.produced.get(chemical).copied().unwrap_or(0);
+        produced.saturating_sub(self.consumed.get(chemical).copied().unwrap_or(0))
+    }
+}
+
+/// Reactions indexed by what they produce, checked so that every chemical can
+/// be made from ORE and none is needed to make itself.
+#[derive(Debug, Clone)]
+pub struct Nanofactory {
+    pub reactions: HashMap<String, Reaction>,
+    /// Every produced chemical, each before any chemical it's made from.
+    pub order: Vec<String>,
+}
+
+impl Nanofactory {
+    pub fn new(reactions: Vec<Reaction>) -> Result<Self, ReactionError> {
+        let mut by_output = HashMap::new();
+        for reaction in reactions {
+            let chemical = reaction.output.chemical.clone();
+            if by_output.insert(chemical.clone(), reaction).is_some() {
+                return DuplicateOutputSnafu { chemical }.fail();
+            }
+        }
+
+        for reaction in by_output.values() {
+            for input in &reaction.inputs {
+                if input.chemical != ORE && !by_output.contains_key(&input.chemical) {
+                    return UnproducibleSnafu { chemical: input.chemical.clone() }.fail();
+                }
+            }
+        }
+
+        let order = topological_order(&by_output)?;
+        Ok(Nanofactory { reactions: by_output, order })
+    }
+
+    /// Runs the reactions needed for `quantity` of `chemical`, starting with
+    /// the chemicals nothing else left needs so every total is known before
+    /// its reaction runs. Surplus from rounding up to whole reactions is left over.
+    pub fn produce(&self, chemical: &str, quantity: u64) -> Result<Production, ReactionError> {
+        if chemical != ORE && !self.reactions.contains_key(chemical) {
+            return UnproducibleSnafu { chemical }.fail();
+        }
+
+        let mut production = Production::default();
+        let mut needed: HashMap<&str, u64> = HashMap::from([(chemical, quantity)]);
+
+        for name in &self.order {
+            let Some(need) = needed.get(name.as_str()).copied() else {
+                continue;
+            };
+            let reaction = &self.reactions[name];
+            let runs = need.div_ceil(reaction.output.quantity);
+            if runs == 0 {
+                continue;
+            }
+            *production.produced.entry(name.clone()).or_default() += runs * reaction.output.quantity;
+            for input in &reaction.inputs {
+                *needed.entry(&input.chemical).or_default() += runs * input.quantity;
+                *production.consumed.entry(input.chemical.clone()).or_default() += runs * input.quantity;
+            }
+        }
+
+        *production.consumed.entry(chemical.to_owned()).or_default() += quantity;
+        Ok(production)
+    }
+
+    /// Length of the longest chain of reactions from ORE to each chemical,
+    /// with ORE itself at 0.
+    pub fn depths(&self) -> HashMap<String, usize> {
+        let mut depths = HashMap::from([(ORE.to_owned(), 0)]);
+        for name in self.order.iter().rev() {
+            let depth = self.reactions[name].inputs.iter().map(|i| depths[&i.chemical] + 1).max().unwrap_or(1);
+            depths.insert(name.clone(), depth);
+        }
+        depths
+    }
+
+    /// Graphviz digraph with an edge from each input to what it makes,
+    /// labelled with the quantity used per reaction. Nodes show how much one
+    /// reaction makes and their depth, and share a rank with the same depth.
+    pub fn dot(&self) -> String {
+        let depths = self.depths();
+        let mut by_depth: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+        for (name, depth) in &depths {
+            by_depth.entry(*depth).or_default().push(name);
+        }
+
+        let mut out = "digraph reactions {\n    rankdir=BT;\n    node [shape=box];\n".to_owned();
+        for (depth, names) in &mut by_depth {
+            names.sort_unstable();
+            for name in names.iter() {
+                let makes = self.reactions.get(*name).map(|r| format!("\\nmakes {}", r.output.quantity));
+                out.push_str(&format!(
+                    "    \"{name}\" [label=\"{name}{}\\ndepth {depth}\"];\n",
+                    makes.unwrap_or_default()
+                ));
+            }
+            let quoted = names.iter().map(|n| format!("\"{n}\"")).collect::<Vec<String>>();
+            out.push_str(&format!("    {{ rank=same; {} }}\n", quoted.join("; ")));
+        }
+        let mut outputs = self.reactions.keys().collect::<Vec<&String>>();
+        outputs.sort_unstable();
+        for output in outputs {
+            for input in &self.reactions[output].inputs {
+                out.push_str(&format!("    \"{}\" -> \"{output}\" [label=\"{}\"];\n", input.chemical, input.quantity));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Table of what `production` made, used and left over of each chemical,
+    /// from ORE up.
+    pub fn report(&self, production: &Production) -> String {
+        let depths = self.depths();
+        let mut chemicals = production.produced.keys().chain(production.consumed.keys()).collect::<Vec<&String>>();
+        chemicals.sort_by_key(|c| (depths.get(*c).copied().unwrap_or(0), *c));
+        chemicals.dedup();
+
+        let mut out = format!("{:<8} {:>5} {:>14} {:>14} {:>8}\n", "chemical", "depth", "produced", "consumed", "leftover");
+        for chemical in chemicals {
+            out.push_str(&format!(
+                "{chemical:<8} {:>5} {:>14} {:>14} {:>8}\n",
+                depths.get(chemical).copied().unwrap_or(0),
+                production.produced.get(chemical).copied().unwrap_or(0),
+                production.consumed.get(chemical).copied().unwrap_or(0),
+                production.leftover(chemical)
+            ));
+        }
+        out
+    }
+
+    /// ORE needed to make `fuel` FUEL.
+    pub fn ore_for_fuel(&self, fuel: u64) -> Result<u64, ReactionError> {
+        Ok(self.produce(FUEL, fuel)?.ore())
+    }
+
+    /// Most FUEL that can be made from `ore` ORE.
+    pub fn max_fuel(&self, ore: u64) -> Result<u64, ReactionError> {
+        let per_fuel = self.ore_for_fuel(1)?;
+        if per_fuel == 0 || per_fuel > ore {
+            return Ok(0);
+        }
+
+        // Leftovers only ever help, so `ore / per_fuel` is always affordable.
+        let (mut lo, mut hi) = (ore / per_fuel, ore / per_fuel * 2 + 1);
+        while self.ore_for_fuel(hi)? <= ore {
+            lo = hi;
+            hi *= 2;
+        }
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if self.ore_for_fuel(mid)? <= ore {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Ok(lo)
+    }
+}
+
+/// Orders chemicals so that each comes before everything it's made from,
+/// failing with the chemicals on a cycle if there is one.
+fn topological_order(reactions: &HashMap<String, Reaction>) -> Result<Vec<String>, ReactionError> {
+    let mut consumers: HashMap<&str, usize> = reactions.keys().map(|k| (k.as_str(), 0)).collect();
+    for reaction in reactions.values() {
+        for input in &reaction.inputs {
+            if let Some(count) = consumers.get_mut(input.chemical.as_str()) {
+                *count += 1;
+            }
+        }
+    }
+
+    let mut ready = consumers.iter().filter(|(_, c)| **c == 0).map(|(k, _)| *k).collect::<Vec<&str>>();
+    ready.sort_unstable();
+    let mut order = vec![];
+    while let Some(chemical) = ready.pop() {
+        order.push(chemical.to_owned());
+        for input in &reactions[chemical].inputs {
+            if let Some(count) = consumers.get_mut(input.chemical.as_str()) {
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(&input.chemical);
+                }
+            }
+        }
+    }
+
+    if order.len() < reactions.len() {
+        return CycleSnafu { chemicals: find_cycle(reactions, &consumers) }.fail();
+    }
+    Ok(order)
+}
+
+/// Walks from a chemical that never became ready to one of its consumers
+/// that didn't either, until one repeats. Returns the cycle with each
+/// chemical followed by one it's made from.
+fn find_cycle(reactions: &HashMap<String, Reaction>, consumers: &HashMap<&str, usize>) -> Vec<String> {
+    let blocked = |chemical: &str| consumers.get(chemical).is_some_and(|c| *c > 0);
+    let Some(mut chemical) = consumers.keys().filter(|k| blocked(k)).min().copied() else {
+        return vec![];
+    };
+    let mut path: Vec<&str> = vec![];
+    while !path.contains(&chemical) {
+        path.push(chemical);
+        let mut next = reactions
+            .iter()
+            .filter(|(name, reaction)| blocked(name) && reaction.inputs.iter().any(|i| i.chemical == chemical))
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<&str>>();
+        next.sort_unstable();
+        let Some(next) = next.first() else {
+            return vec![];
+        };
+        chemical = next;
+    }
+    let start = path.iter().position(|c| *c == chemical).unwrap_or(0);
+    let mut cycle = vec![chemical.to_owned()];
+    cycle.extend(path[start..].iter().rev().map(|c| c.to_string()));
+    cycle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIMPLE: &str = "10 ORE => 10 A
+1 ORE => 1 B
+7 A, 1 B => 1 C
+7 A, 1 C => 1 D
+7 A, 1 D => 1 E
+7 A, 1 E => 1 FUEL";
+
+    const LARGER: &str = "157 ORE => 5 NZVS
+165 ORE => 6 DCFZ
+44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
+12 HKGWZ, 1 GPVTF, 8 PSHF => 9 QDVJ
+179 ORE => 7 PSHF
+177 ORE => 5 HKGWZ
+7 DCFZ, 7 PSHF => 2 XJWVT
+165 ORE => 2 GPVTF
+3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT";
+
+    fn factory(input: &str) -> Nanofactory {
+        Nanofactory::new(parse(input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parses_reactions() {
+        let reactions = parse("3 NPNGZ, 3 TBFQ, 1 RZBF => 2 LQNR\n\n").unwrap();
+        assert_eq!(reactions.len(), 1);
+        assert_eq!(reactions[0].to_string(), "3 NPNGZ, 3 TBFQ, 1 RZBF => 2 LQNR");
+    }
+
+    #[test]
+    fn computes_ore_for_examples() {
+        assert_eq!(factory(SIMPLE).ore_for_fuel(1).unwrap(), 31);
+        assert_eq!(factory(LARGER).ore_for_fuel(1).unwrap(), 13312);
+    }
+
+    #[test]
+    fn finds_max_fuel() {
+        assert_eq!(factory(LARGER).max_fuel(1_000_000_000_000).unwrap(), 82892753);
+        assert_eq!(factory(LARGER).max_fuel(13311).unwrap(), 0);
+    }
+
+    #[test]
+    fn tracks_leftovers() {
+        let production = factory(SIMPLE).produce(FUEL, 1).unwrap();
+        assert_eq!(production.ore(), 31);
+        assert_eq!(production.leftover("A"), 2);
+        assert_eq!(production.leftover(FUEL), 0);
+    }
+
+    #[test]
+    fn rejects_parse_errors() {
+        let err = parse("10 ORE => 10 A\n7 A 1 B => 1 C").unwrap_err();
+        assert!(matches!(&err, ReactionError::Parse { line: 2, text } if text == "7 A 1 B => 1 C"));
+        assert!(matches!(parse("10 ORE =>"), Err(ReactionError::Parse { line: 1, .. })));
+    }
+
+    #[test]
+    fn rejects_unproducible_chemicals() {
+        let err = Nanofactory::new(parse("10 ORE => 10 A\n7 A, 1 B => 1 FUEL").unwrap()).unwrap_err();
+        assert!(matches!(&err, ReactionError::Unproducible { chemical } if chemical == "B"));
+        assert!(matches!(factory(SIMPLE).produce("Z", 1), Err(ReactionError::Unproducible { .. })));
+    }
+
+    #[test]
+    fn rejects_duplicate_outputs() {
+        let err = Nanofactory::new(parse("10 ORE => 10 A\n1 ORE => 1 A").unwrap()).unwrap_err();
+        assert!(matches!(&err, ReactionError::DuplicateOutput { chemical } if chemical == "A"));
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let err = Nanofactory::new(parse("1 ORE, 1 B => 1 A\n1 A => 1 B\n1 A => 1 FUEL").unwrap()).unwrap_err();
+        let ReactionError::Cycle { chemicals } = err else {
+            panic!("expected a cycle, got {err}");
+        };
+        assert_eq!(chemicals.first(), chemicals.last());
+        assert!(chemicals.contains(&"A".to_owned()) && chemicals.contains(&"B".to_owned()));
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Terminal output collected as an asciicast v2 recording, replayable with
+/// `asciinema play`.
+#[derive(Debug, Clone)]
+pub struct Asciicast {
+    pub width: usize,
+    pub height: usize,
+    /// Seconds from the start of the recording and the text written then.
+    pub events: Vec<(f64, String)>,
+}
+
+impl Asciicast {
+    pub fn new(width: usize, height: usize) -> Self {
+        Asciicast {
+            width,
+            height,
+            events: vec![],
+        }
+    }
+
+    /// Adds a frame that redraws the screen in place from the top left corner.
+    /// The first frame clears the screen as well.
+    pub fn frame(&mut self, time: f64, text: &str) {
+        let clear = if self.events.is_empty() { "\x1b[2J" } else { "" };
+        self.events.push((time, format!("{clear}\x1b[H{}", text.replace('\n', "\r\n"))));
+    }
+
+    /// Writes the header line followed by one output event per line.
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, r#"{{"version": 2, "width": {}, "height": {}}}"#, self.width, self.height)?;
+        for (time, text) in &self.events {
+            writeln!(out, r#"[{time:.6}, "o", {}]"#, json_string(text))?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        self.write_to(&mut out)?;
+        out.flush()
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Writes images into a directory as `frame-00000.ppm`, `frame-00001.ppm`, ...
+#[derive(Debug, Clone)]
+pub struct FrameDir {
+    dir: PathBuf,
+    count: usize,
+}
+
+impl FrameDir {
+    /// Creates `dir` if it doesn't exist yet.
+    pub fn create(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(FrameDir {
+            dir: dir.to_path_buf(),
+            count: 0,
+        })
+    }
+
+    pub fn frame(&mut self, image: &[u8]) -> io::Result<()> {
+        fs::write(self.dir.join(format!("frame-{:05}.ppm", self.count)), image)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Number of frames written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
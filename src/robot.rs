@@ -0,0 +1,168 @@
+use anyhow::bail;
+
+use crate::geom::{Direction, Point2, Turn};
+use crate::grid::Grid;
+use crate::intcode::{process, ProgramState, StopCode};
+
+impl TryFrom<i64> for Turn {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i64) -> anyhow::Result<Turn> {
+        match value {
+            0 => Ok(Turn::LEFT),
+            1 => Ok(Turn::RIGHT),
+            _ => bail!("Bad turn {value}"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Color {
+    WHITE,
+    #[default]
+    BLACK,
+}
+
+impl TryFrom<i64> for Color {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i64) -> anyhow::Result<Color> {
+        match value {
+            0 => Ok(Color::BLACK),
+            1 => Ok(Color::WHITE),
+            _ => bail!("Bad color {value}"),
+        }
+    }
+}
+
+impl From<Color> for i64 {
+    fn from(value: Color) -> Self {
+        match value {
+            Color::WHITE => 1,
+            Color::BLACK => 0,
+        }
+    }
+}
+
+/// One move of the robot: where it was, which way it faced, the colour it
+/// saw, the colour it painted and which way it turned afterwards.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Step {
+    pub pos: Point2,
+    pub facing: Direction,
+    pub read: Color,
+    pub painted: Color,
+    pub turn: Turn,
+}
+
+#[derive(Debug, Clone)]
+pub struct Robot {
+    pub state: ProgramState,
+    pub map: Grid<Color>,
+    pub pos: Point2,
+    pub facing: Direction,
+    /// Every step taken, if recording was asked for.
+    pub history: Option<Vec<Step>>,
+}
+
+impl Robot {
+    /// A robot at the origin facing up, on a hull where the starting panel is `start`.
+    pub fn new(program: &[i64], start: Color) -> Self {
+        let mut map = Grid::sparse();
+        map.insert(Point2::ORIGIN, start);
+        Robot {
+            state: ProgramState {
+                memory: program.to_vec(),
+                ..Default::default()
+            },
+            map,
+            pos: Point2::ORIGIN,
+            facing: Direction::UP,
+            history: None,
+        }
+    }
+
+    /// Keeps every step in `history` from now on.
+    pub fn record(&mut self) {
+        self.history.get_or_insert_with(Vec::new);
+    }
+
+    /// Reads the panel, paints it and moves on. Returns `false` once the
+    /// program has halted.
+    pub fn step(&mut self) -> anyhow::Result<bool> {
+        let read = *self.map.get(self.pos).unwrap_or(&Color::BLACK);
+        self.state.input = read.into();
+        let Some(painted) = process(&mut self.state)? else {
+            return Ok(false);
+        };
+        let painted = Color::try_from(painted)?;
+        let Some(turn) = process(&mut self.state)? else {
+            bail!("Halted after painting {} without turning", self.pos);
+        };
+        let turn = Turn::try_from(turn)?;
+
+        if let Some(history) = &mut self.history {
+            history.push(Step {
+                pos: self.pos,
+                facing: self.facing,
+                read,
+                painted,
+                turn,
+            });
+        }
+        self.map.insert(self.pos, painted);
+        self.facing = self.facing.turn(turn);
+        self.pos = self.pos.step(self.facing);
+        Ok(true)
+    }
+
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        while self.state.stop_code == StopCode::RUN && self.step()? {}
+        Ok(())
+    }
+}
+
+/// How many times each panel was painted.
+pub fn paint_counts(history: &[Step]) -> Grid<usize> {
+    let mut counts = Grid::sparse();
+    for step in history {
+        match counts.get_mut(step.pos) {
+            Some(count) => *count += 1,
+            None => {
+                counts.insert(step.pos, 1);
+            }
+        }
+    }
+    counts
+}
+
+/// Plays `history` back from a hull where the starting panel is `start`,
+/// calling `frame` with the hull and the robot before every step and once at
+/// the end. Fails if the robot didn't follow the rules: moving one panel in
+/// the direction it turned to and seeing the colour last painted there.
+pub fn replay(
+    history: &[Step],
+    start: Color,
+    mut frame: impl FnMut(&Grid<Color>, Point2, Direction) -> anyhow::Result<()>,
+) -> anyhow::Result<Grid<Color>> {
+    let mut map = Grid::sparse();
+    map.insert(Point2::ORIGIN, start);
+    let (mut pos, mut facing) = (Point2::ORIGIN, Direction::UP);
+
+    for (i, step) in history.iter().enumerate() {
+        frame(&map, pos, facing)?;
+        if (step.pos, step.facing) != (pos, facing) {
+            bail!("Step {i}: robot at {} facing {:?}, expected {pos} facing {facing:?}", step.pos, step.facing);
+        }
+        let seen = *map.get(pos).unwrap_or(&Color::BLACK);
+        if step.read != seen {
+            bail!("Step {i}: robot read {:?} at {pos} but the panel is {seen:?}", step.read);
+        }
+        map.insert(pos, step.painted);
+        facing = facing.turn(step.turn);
+        pos = pos.step(facing);
+    }
+
+    frame(&map, pos, facing)?;
+    Ok(map)
+}
@@ -0,0 +1,56 @@
+/// Test applied to each candidate cell, comparing its value at the last scan
+/// with its current one.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Predicate {
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+    Equals(i64),
+}
+
+impl Predicate {
+    fn test(&self, old: i64, new: i64) -> bool {
+        match self {
+            Predicate::Changed => old != new,
+            Predicate::Unchanged => old == new,
+            Predicate::Increased => new > old,
+            Predicate::Decreased => new < old,
+            Predicate::Equals(x) => new == *x,
+        }
+    }
+}
+
+/// Narrows down which memory cells hold a value by filtering on successive snapshots.
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    candidates: Vec<usize>,
+    snapshot: Vec<i64>,
+}
+
+impl Scanner {
+    /// Starts with every address in `memory` as a candidate.
+    pub fn new(memory: &[i64]) -> Self {
+        Scanner {
+            candidates: (0..memory.len()).collect(),
+            snapshot: memory.to_vec(),
+        }
+    }
+
+    /// Keeps the candidates matching `predicate` and takes a new snapshot.
+    /// Returns how many candidates are left.
+    pub fn scan(&mut self, memory: &[i64], predicate: Predicate) -> usize {
+        let snapshot = &self.snapshot;
+        self.candidates.retain(|addr| {
+            let old = snapshot.get(*addr).copied().unwrap_or(0);
+            let new = memory.get(*addr).copied().unwrap_or(0);
+            predicate.test(old, new)
+        });
+        self.snapshot = memory.to_vec();
+        self.candidates.len()
+    }
+
+    pub fn candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+}
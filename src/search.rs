@@ -0,0 +1,234 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Nodes from the start to the goal, both included, and what it cost to get there.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Route<N> {
+    pub nodes: Vec<N>,
+    pub cost: u64,
+}
+
+impl<N> Route<N> {
+    /// Number of moves taken, one less than the number of nodes.
+    pub fn steps(&self) -> usize {
+        self.nodes.len().saturating_sub(1)
+    }
+}
+
+/// Follows `parents` back from `end` to the node with no parent.
+fn reconstruct<N: Eq + Hash + Clone>(parents: &HashMap<N, N>, end: N) -> Vec<N> {
+    let mut nodes = vec![end];
+    while let Some(parent) = parents.get(nodes.last().expect("starts with end")) {
+        nodes.push(parent.clone());
+    }
+    nodes.reverse();
+    nodes
+}
+
+/// Fewest moves from `start` to a node `is_goal` accepts, with every move costing 1.
+pub fn bfs<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I, mut is_goal: impl FnMut(&N) -> bool) -> Option<Route<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents: HashMap<N, N> = HashMap::new();
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node];
+        if is_goal(&node) {
+            return Some(Route {
+                nodes: reconstruct(&parents, node),
+                cost: distance,
+            });
+        }
+        for next in neighbours(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                parents.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Moves from `start` to every node reachable from it, each move costing 1.
+pub fn flood_fill<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> HashMap<N, u64>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node];
+        for next in neighbours(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// Cheapest route from `start` to a node `is_goal` accepts, where `neighbours`
+/// gives each next node with the cost of moving there.
+pub fn dijkstra<N, I>(start: N, neighbours: impl FnMut(&N) -> I, is_goal: impl FnMut(&N) -> bool) -> Option<Route<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    astar(start, neighbours, |_| 0, is_goal)
+}
+
+/// Like [`dijkstra`], exploring first the nodes `heuristic` estimates are
+/// closest to a goal. The route is cheapest as long as the heuristic never
+/// overestimates.
+pub fn astar<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> u64,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Route<N>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut parents: HashMap<N, N> = HashMap::new();
+    let mut costs = HashMap::from([(start.clone(), 0)]);
+    // Queued nodes live in `nodes` so the heap only has to order numbers.
+    let mut nodes = vec![start.clone()];
+    let mut queue = BinaryHeap::from([Reverse((heuristic(&start), 0, 0))]);
+
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        let node = nodes[index].clone();
+        if cost > costs[&node] {
+            continue;
+        }
+        if is_goal(&node) {
+            return Some(Route {
+                nodes: reconstruct(&parents, node),
+                cost,
+            });
+        }
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            if costs.get(&next).is_some_and(|known| *known <= next_cost) {
+                continue;
+            }
+            costs.insert(next.clone(), next_cost);
+            parents.insert(next.clone(), node.clone());
+            queue.push(Reverse((next_cost + heuristic(&next), next_cost, nodes.len())));
+            nodes.push(next);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Point2;
+    use crate::grid::Grid;
+
+    const ORBITS: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN";
+
+    fn orbit_graph() -> HashMap<&'static str, Vec<&'static str>> {
+        let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+        for line in ORBITS.lines() {
+            let (center, satellite) = line.split_once(')').unwrap();
+            graph.entry(center).or_default().push(satellite);
+            graph.entry(satellite).or_default().push(center);
+        }
+        graph
+    }
+
+    fn maze(rows: &str) -> Grid<char> {
+        Grid::parse(rows, |_, c| Some(c))
+    }
+
+    fn find(maze: &Grid<char>, target: char) -> Point2 {
+        maze.iter().find(|(_, c)| **c == target).unwrap().0
+    }
+
+    fn open(maze: &Grid<char>, pos: &Point2) -> Vec<Point2> {
+        maze.neighbours(*pos).filter(|(_, c)| **c != '#').map(|(p, _)| p).collect()
+    }
+
+    #[test]
+    fn bfs_counts_orbital_transfers() {
+        let graph = orbit_graph();
+        let route = bfs("YOU", |n| graph[n].clone(), |n| *n == "SAN").unwrap();
+        assert_eq!(route.nodes, ["YOU", "K", "J", "E", "D", "I", "SAN"]);
+        assert_eq!(route.steps() - 2, 4);
+    }
+
+    #[test]
+    fn flood_fill_counts_orbits() {
+        let graph = orbit_graph();
+        let depths = flood_fill("COM", |n| graph[n].clone());
+        let without_you_and_san = depths.iter().filter(|(n, _)| !["YOU", "SAN"].contains(n)).map(|(_, d)| d).sum::<u64>();
+        assert_eq!(without_you_and_san, 42);
+    }
+
+    #[test]
+    fn bfs_finds_shortest_path_through_maze() {
+        let maze = maze("#######\n#S..#.#\n#.#.#.#\n#.#...#\n#...#E#\n#######");
+        let (start, end) = (find(&maze, 'S'), find(&maze, 'E'));
+        let route = bfs(start, |p| open(&maze, p), |p| *p == end).unwrap();
+        assert_eq!(route.cost, 7);
+        assert_eq!(route.nodes.first(), Some(&start));
+        assert_eq!(route.nodes.last(), Some(&end));
+        for pair in route.nodes.windows(2) {
+            assert_eq!(pair[0].manhattan(pair[1]), 1);
+        }
+    }
+
+    #[test]
+    fn bfs_reports_unreachable_goal() {
+        let maze = maze("#####\n#S#E#\n#####");
+        let end = find(&maze, 'E');
+        assert_eq!(bfs(find(&maze, 'S'), |p| open(&maze, p), |p| *p == end), None);
+    }
+
+    #[test]
+    fn flood_fill_measures_maze() {
+        let maze = maze("#####\n#S..#\n#.#.#\n#...#\n#####");
+        let distances = flood_fill(find(&maze, 'S'), |p| open(&maze, p));
+        assert_eq!(distances.len(), 8);
+        assert_eq!(distances.values().max(), Some(&4));
+    }
+
+    #[test]
+    fn dijkstra_prefers_cheap_detour() {
+        // Digits cost that much to enter, everything else 1.
+        let maze = maze("#######\n#S999E#\n#.###.#\n#.....#\n#######");
+        let (start, end) = (find(&maze, 'S'), find(&maze, 'E'));
+        let cost = |p: &Point2| maze.get(*p).and_then(|c| c.to_digit(10)).unwrap_or(1) as u64;
+        let route = dijkstra(start, |p| open(&maze, p).into_iter().map(|n| (n, cost(&n))), |p| *p == end).unwrap();
+        assert_eq!(route.cost, 8);
+        assert!(route.nodes.iter().all(|p| cost(p) == 1));
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() {
+        let maze = maze("##########\n#S.......#\n#.######.#\n#......#.#\n######.#.#\n#E.....#.#\n##########");
+        let (start, end) = (find(&maze, 'S'), find(&maze, 'E'));
+        let steps = |p: &Point2| open(&maze, p).into_iter().map(|n| (n, 1));
+        let plain = dijkstra(start, steps, |p| *p == end).unwrap();
+        let guided = astar(start, steps, |p| p.manhattan(end) as u64, |p| *p == end).unwrap();
+        assert_eq!(guided.cost, plain.cost);
+        assert_eq!(guided.cost, 14);
+    }
+
+    #[test]
+    fn start_is_goal() {
+        let route = bfs(0, |n| [n + 1], |n| *n == 0).unwrap();
+        assert_eq!(route, Route { nodes: vec![0], cost: 0 });
+    }
+}
@@ -0,0 +1,233 @@
+use snafu::{ensure, Snafu};
+
+use crate::export::Rgb;
+use crate::geom::Point2;
+use crate::grid::Grid;
+
+#[derive(Debug, Snafu)]
+pub enum SpaceImageError {
+    #[snafu(display("Image must be at least 1x1, got {width}x{height}"))]
+    ZeroSize { width: usize, height: usize },
+    #[snafu(display("Expected a digit at offset {offset}, found {found:?}"))]
+    NotADigit { offset: usize, found: char },
+    #[snafu(display("Bad pixel {value} at offset {offset}, expected 0, 1 or 2"))]
+    BadPixel { offset: usize, value: u32 },
+    #[snafu(display("{len} pixels is not a whole number of {layer_size} pixel layers"))]
+    PartialLayer { len: usize, layer_size: usize },
+    #[snafu(display("Image has no layers"))]
+    NoLayers,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Pixel {
+    BLACK,
+    WHITE,
+    TRANSPARENT,
+}
+
+impl TryFrom<u32> for Pixel {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Pixel, u32> {
+        match value {
+            0 => Ok(Pixel::BLACK),
+            1 => Ok(Pixel::WHITE),
+            2 => Ok(Pixel::TRANSPARENT),
+            _ => Err(value),
+        }
+    }
+}
+
+impl Pixel {
+    pub fn digit(&self) -> char {
+        match self {
+            Pixel::BLACK => '0',
+            Pixel::WHITE => '1',
+            Pixel::TRANSPARENT => '2',
+        }
+    }
+
+    pub fn glyph(&self) -> char {
+        match self {
+            Pixel::BLACK => ' ',
+            Pixel::WHITE => '█',
+            Pixel::TRANSPARENT => '·',
+        }
+    }
+
+    pub fn color(&self) -> Rgb {
+        match self {
+            Pixel::BLACK => [0, 0, 0],
+            Pixel::WHITE => [255, 255, 255],
+            Pixel::TRANSPARENT => [128, 128, 128],
+        }
+    }
+}
+
+/// An image in the Space Image Format: layers of `width` by `height` pixels,
+/// each stored row by row, the first layer in front.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SpaceImage {
+    pub width: usize,
+    pub height: usize,
+    pub layers: Vec<Vec<Pixel>>,
+}
+
+impl SpaceImage {
+    /// Decodes one digit per pixel. Trailing whitespace is ignored.
+    pub fn decode(input: &str, width: usize, height: usize) -> Result<Self, SpaceImageError> {
+        ensure!(width > 0 && height > 0, ZeroSizeSnafu { width, height });
+
+        let pixels = input
+            .trim_end()
+            .chars()
+            .enumerate()
+            .map(|(offset, c)| {
+                let value = c.to_digit(10).ok_or(SpaceImageError::NotADigit { offset, found: c })?;
+                Pixel::try_from(value).map_err(|value| SpaceImageError::BadPixel { offset, value })
+            })
+            .collect::<Result<Vec<Pixel>, SpaceImageError>>()?;
+
+        let layer_size = width * height;
+        ensure!(!pixels.is_empty(), NoLayersSnafu);
+        ensure!(pixels.len() % layer_size == 0, PartialLayerSnafu { len: pixels.len(), layer_size });
+
+        Ok(SpaceImage {
+            width,
+            height,
+            layers: pixels.chunks(layer_size).map(<[Pixel]>::to_vec).collect(),
+        })
+    }
+
+    /// Like [`decode`](Self::decode), but fills a short last layer with
+    /// transparent pixels instead of failing.
+    pub fn decode_padded(input: &str, width: usize, height: usize) -> Result<Self, SpaceImageError> {
+        let digits = input.trim_end();
+        let layer_size = width.max(1) * height.max(1);
+        let missing = (layer_size - digits.chars().count() % layer_size) % layer_size;
+        if missing > 0 {
+            log::warn!("Padding the last layer with {missing} transparent pixels");
+        }
+        let padded = format!("{digits}{}", Pixel::TRANSPARENT.digit().to_string().repeat(missing));
+        Self::decode(&padded, width, height)
+    }
+
+    /// The digits of every layer in order, without a trailing newline.
+    pub fn encode(&self) -> String {
+        self.layers.iter().flatten().map(Pixel::digit).collect()
+    }
+
+    /// A single layer image covering the bounds of `grid`, with the cells
+    /// it doesn't set left transparent.
+    pub fn from_grid(grid: &Grid<Pixel>) -> Result<Self, SpaceImageError> {
+        let Some(bounds) = grid.bounds() else {
+            return NoLayersSnafu.fail();
+        };
+        let layer = (bounds.min.y..=bounds.max.y)
+            .flat_map(|y| (bounds.min.x..=bounds.max.x).map(move |x| Point2::new(x, y)))
+            .map(|pos| *grid.get(pos).unwrap_or(&Pixel::TRANSPARENT))
+            .collect();
+        Ok(SpaceImage {
+            width: bounds.width(),
+            height: bounds.height(),
+            layers: vec![layer],
+        })
+    }
+
+    /// How many black, white and transparent pixels each layer has, indexed
+    /// by the pixel's digit.
+    pub fn histograms(&self) -> Vec<[usize; 3]> {
+        self.layers
+            .iter()
+            .map(|layer| {
+                let mut counts = [0; 3];
+                for pixel in layer {
+                    counts[*pixel as usize] += 1;
+                }
+                counts
+            })
+            .collect()
+    }
+
+    /// White times transparent pixels on the layer with the fewest black ones.
+    pub fn checksum(&self) -> usize {
+        let histograms = self.histograms();
+        let counts = histograms.iter().min_by_key(|counts| counts[Pixel::BLACK as usize]);
+        counts.map(|counts| counts[Pixel::WHITE as usize] * counts[Pixel::TRANSPARENT as usize]).unwrap_or(0)
+    }
+
+    /// Pixel at `x`, `y` of `layer`.
+    pub fn pixel(&self, layer: usize, x: usize, y: usize) -> Option<Pixel> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.layers.get(layer).map(|pixels| pixels[y * self.width + x])
+    }
+
+    /// The image as seen from the front: each pixel is the first one that
+    /// isn't transparent, or transparent if none is.
+    pub fn composite(&self) -> Grid<Pixel> {
+        let mut image = Grid::dense(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = y * self.width + x;
+                let pixel = self.layers.iter().map(|layer| layer[index]).find(|p| *p != Pixel::TRANSPARENT);
+                image.insert(Point2::new(x as i64, y as i64), pixel.unwrap_or(Pixel::TRANSPARENT));
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_layers() {
+        let image = SpaceImage::decode("123456789012", 3, 2);
+        assert!(matches!(image, Err(SpaceImageError::BadPixel { offset: 2, value: 3 })));
+
+        let image = SpaceImage::decode("012210\n", 3, 2).unwrap();
+        assert_eq!(image.layers.len(), 1);
+        assert_eq!(image.pixel(0, 2, 0), Some(Pixel::TRANSPARENT));
+        assert_eq!(image.pixel(0, 0, 1), Some(Pixel::TRANSPARENT));
+        assert_eq!(image.pixel(0, 3, 0), None);
+        assert_eq!(image.histograms(), [[2, 2, 2]]);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(matches!(SpaceImage::decode("0120", 0, 2), Err(SpaceImageError::ZeroSize { .. })));
+        assert!(matches!(SpaceImage::decode("01x0", 2, 2), Err(SpaceImageError::NotADigit { offset: 2, found: 'x' })));
+        assert!(matches!(SpaceImage::decode("01201", 2, 2), Err(SpaceImageError::PartialLayer { len: 5, layer_size: 4 })));
+        assert!(matches!(SpaceImage::decode("\n", 2, 2), Err(SpaceImageError::NoLayers)));
+
+        let padded = SpaceImage::decode_padded("01201", 2, 2).unwrap();
+        assert_eq!(padded.encode(), "01201222");
+    }
+
+    #[test]
+    fn composites_front_to_back() {
+        let image = SpaceImage::decode("0222112222120000", 2, 2).unwrap();
+        let composite = image.composite();
+        assert_eq!(composite.render(|p| p.map_or('?', Pixel::digit)), "01\n10\n");
+        assert_eq!(image.checksum(), 2 * 2);
+    }
+
+    #[test]
+    fn round_trips_through_grid() {
+        let image = SpaceImage::decode("0222112222120000", 2, 2).unwrap();
+        let flattened = SpaceImage::from_grid(&image.composite()).unwrap();
+        assert_eq!(flattened.encode(), "0110");
+
+        let mut grid = Grid::sparse();
+        grid.insert(Point2::new(-1, 3), Pixel::WHITE);
+        grid.insert(Point2::new(1, 4), Pixel::BLACK);
+        let image = SpaceImage::from_grid(&grid).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(image.encode(), "122220");
+        assert_eq!(SpaceImage::decode(&image.encode(), 3, 2).unwrap(), image);
+        assert!(matches!(SpaceImage::from_grid(&Grid::sparse()), Err(SpaceImageError::NoLayers)));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::arcade::Arcade;
+use crate::geom::Point2;
+
+/// Decides which way to push the joystick, -1 for left, 0 to stay and 1 for
+/// right, each time the game asks for it. The arcade keeps track of the ball
+/// and paddle as they're drawn, so strategies needn't search the screen.
+pub trait JoystickStrategy {
+    fn name(&self) -> &str;
+
+    fn joystick(&mut self, arcade: &Arcade) -> i64;
+}
+
+fn toward(from: i64, to: i64) -> i64 {
+    (to - from).signum()
+}
+
+/// Moves the paddle under the ball.
+#[derive(Debug, Clone, Default)]
+pub struct FollowBall;
+
+impl JoystickStrategy for FollowBall {
+    fn name(&self) -> &str {
+        "follow"
+    }
+
+    fn joystick(&mut self, arcade: &Arcade) -> i64 {
+        match (arcade.paddle, arcade.ball) {
+            (Some(paddle), Some(ball)) => toward(paddle.x, ball.x),
+            _ => 0,
+        }
+    }
+}
+
+/// Moves the paddle to where the ball will come down, working it out from the
+/// ball's last move and bouncing it off the side walls. Blocks in the way
+/// aren't accounted for, so the guess is refined as the ball gets closer.
+#[derive(Debug, Clone, Default)]
+pub struct PredictLanding;
+
+impl JoystickStrategy for PredictLanding {
+    fn name(&self) -> &str {
+        "predict"
+    }
+
+    fn joystick(&mut self, arcade: &Arcade) -> i64 {
+        let (Some(paddle), Some(ball)) = (arcade.paddle, arcade.ball) else {
+            return 0;
+        };
+        let last = arcade.last_ball.unwrap_or(ball);
+        let (Point2 { x: paddle_x, y: paddle_y }, Point2 { x, y }) = (paddle, ball);
+        let Point2 { x: dx, y: dy } = ball - last;
+
+        if dy <= 0 {
+            return toward(paddle_x, x);
+        }
+
+        let (lo, hi) = (1, arcade.max_x - 1);
+        let period = 2 * (hi - lo);
+        if period <= 0 {
+            return toward(paddle_x, x);
+        }
+        let travelled = (x - lo + dx * (paddle_y - 1 - y)).rem_euclid(period);
+        let landing = lo + if travelled > hi - lo { period - travelled } else { travelled };
+
+        toward(paddle_x, landing)
+    }
+}
+
+/// Pushes the joystick at random, from a xorshift generator so runs repeat
+/// for the same seed.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random { state: seed.max(1) }
+    }
+}
+
+impl JoystickStrategy for Random {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn joystick(&mut self, _arcade: &Arcade) -> i64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state % 3) as i64 - 1
+    }
+}
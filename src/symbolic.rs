@@ -0,0 +1,520 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Rem;
+use std::rc::Rc;
+
+use anyhow::{bail, Context};
+use itertools::Itertools;
+
+use crate::intcode::{get_mode, param_count, Mode};
+
+/// Value of a memory cell in terms of the inputs read so far.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr {
+    Const(i64),
+    Input(usize),
+    Add(Rc<Expr>, Rc<Expr>),
+    Mul(Rc<Expr>, Rc<Expr>),
+    Lt(Rc<Expr>, Rc<Expr>),
+    Eq(Rc<Expr>, Rc<Expr>),
+}
+
+/// `a * x + b` where `x` is the input `symbol`, or just `b` if there is none.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Linear {
+    symbol: Option<usize>,
+    a: i64,
+    b: i64,
+}
+
+impl Linear {
+    fn combine(self, other: Self, sign: i64) -> Option<Self> {
+        let symbol = match (self.symbol, other.symbol) {
+            (Some(x), Some(y)) if x != y => return None,
+            (x, y) => x.or(y),
+        };
+        Some(Linear {
+            symbol,
+            a: self.a.checked_add(other.a.checked_mul(sign)?)?,
+            b: self.b.checked_add(other.b.checked_mul(sign)?)?,
+        })
+    }
+
+    /// Integer values of the symbol around the root of `a * x + b = 0`.
+    fn roots(&self) -> Vec<(usize, i64)> {
+        match self.symbol {
+            Some(symbol) if self.a != 0 => {
+                let root = (-self.b).div_euclid(self.a);
+                (root - 1..=root + 1).map(|x| (symbol, x)).collect()
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl Expr {
+    pub fn sum(x: Rc<Expr>, y: Rc<Expr>) -> Rc<Expr> {
+        match (x.as_ref(), y.as_ref()) {
+            (Expr::Const(a), Expr::Const(b)) => Rc::new(Expr::Const(a.wrapping_add(*b))),
+            (Expr::Const(0), _) => y,
+            (_, Expr::Const(0)) => x,
+            _ => Rc::new(Expr::Add(x, y)),
+        }
+    }
+
+    pub fn product(x: Rc<Expr>, y: Rc<Expr>) -> Rc<Expr> {
+        match (x.as_ref(), y.as_ref()) {
+            (Expr::Const(a), Expr::Const(b)) => Rc::new(Expr::Const(a.wrapping_mul(*b))),
+            (Expr::Const(0), _) | (_, Expr::Const(0)) => Rc::new(Expr::Const(0)),
+            (Expr::Const(1), _) => y,
+            (_, Expr::Const(1)) => x,
+            _ => Rc::new(Expr::Mul(x, y)),
+        }
+    }
+
+    pub fn less_than(x: Rc<Expr>, y: Rc<Expr>) -> Rc<Expr> {
+        match (x.as_ref(), y.as_ref()) {
+            (Expr::Const(a), Expr::Const(b)) => Rc::new(Expr::Const((a < b) as i64)),
+            _ => Rc::new(Expr::Lt(x, y)),
+        }
+    }
+
+    pub fn equals(x: Rc<Expr>, y: Rc<Expr>) -> Rc<Expr> {
+        match (x.as_ref(), y.as_ref()) {
+            (Expr::Const(a), Expr::Const(b)) => Rc::new(Expr::Const((a == b) as i64)),
+            _ if x == y => Rc::new(Expr::Const(1)),
+            _ => Rc::new(Expr::Eq(x, y)),
+        }
+    }
+
+    pub fn as_const(&self) -> Option<i64> {
+        match self {
+            Expr::Const(c) => Some(*c),
+            _ => None,
+        }
+    }
+
+    pub fn eval(&self, inputs: &[i64]) -> i64 {
+        match self {
+            Expr::Const(c) => *c,
+            Expr::Input(n) => inputs.get(*n).copied().unwrap_or(0),
+            Expr::Add(x, y) => x.eval(inputs).wrapping_add(y.eval(inputs)),
+            Expr::Mul(x, y) => x.eval(inputs).wrapping_mul(y.eval(inputs)),
+            Expr::Lt(x, y) => (x.eval(inputs) < y.eval(inputs)) as i64,
+            Expr::Eq(x, y) => (x.eval(inputs) == y.eval(inputs)) as i64,
+        }
+    }
+
+    fn linear(&self) -> Option<Linear> {
+        match self {
+            Expr::Const(c) => Some(Linear { symbol: None, a: 0, b: *c }),
+            Expr::Input(n) => Some(Linear { symbol: Some(*n), a: 1, b: 0 }),
+            Expr::Add(x, y) => x.linear()?.combine(y.linear()?, 1),
+            Expr::Mul(x, y) => {
+                let (x, y) = (x.linear()?, y.linear()?);
+                let (c, l) = match (x.symbol, y.symbol) {
+                    (None, _) => (x.b, y),
+                    (_, None) => (y.b, x),
+                    _ => return None,
+                };
+                Some(Linear { symbol: l.symbol, a: l.a.checked_mul(c)?, b: l.b.checked_mul(c)? })
+            }
+            Expr::Lt(_, _) | Expr::Eq(_, _) => None,
+        }
+    }
+
+    /// The one input a condition is linear in, or `Some(None)` for a constant,
+    /// if it's simple enough that [`solve`] is exact for it.
+    fn exact_symbol(&self) -> Option<Option<usize>> {
+        match self {
+            Expr::Lt(x, y) | Expr::Eq(x, y) => Some(x.linear()?.combine(y.linear()?, -1)?.symbol),
+            _ => Some(self.linear()?.symbol),
+        }
+    }
+
+    /// Candidate input values that sit on the boundaries of this expression's comparisons.
+    fn candidates(&self, acc: &mut Vec<(usize, i64)>) {
+        match self {
+            Expr::Const(_) | Expr::Input(_) => {}
+            Expr::Add(x, y) | Expr::Mul(x, y) => {
+                x.candidates(acc);
+                y.candidates(acc);
+            }
+            Expr::Lt(x, y) | Expr::Eq(x, y) => {
+                if let Some(diff) = x.linear().zip(y.linear()).and_then(|(x, y)| x.combine(y, -1)) {
+                    acc.extend(diff.roots());
+                }
+                x.candidates(acc);
+                y.candidates(acc);
+            }
+        }
+        if let Some(l) = self.linear() {
+            acc.extend(l.roots());
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Const(c) => write!(f, "{c}"),
+            Expr::Input(n) => write!(f, "in{n}"),
+            Expr::Add(x, y) => write!(f, "({x} + {y})"),
+            Expr::Mul(x, y) => write!(f, "({x} * {y})"),
+            Expr::Lt(x, y) => write!(f, "({x} < {y})"),
+            Expr::Eq(x, y) => write!(f, "({x} == {y})"),
+        }
+    }
+}
+
+/// Branch decision on a path: `cond` was non-zero if `holds`, zero otherwise.
+#[derive(Debug, Clone)]
+pub struct Constraint {
+    pub cond: Rc<Expr>,
+    pub holds: bool,
+}
+
+impl Constraint {
+    fn check(&self, inputs: &[i64]) -> bool {
+        (self.cond.eval(inputs) != 0) == self.holds
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.holds {
+            true => write!(f, "{} != 0", self.cond),
+            false => write!(f, "{} == 0", self.cond),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SymbolicState {
+    pub memory: Vec<Rc<Expr>>,
+    pub func_ptr: usize,
+    pub relative_base: i64,
+    pub inputs: usize,
+    pub outputs: Vec<Rc<Expr>>,
+    pub constraints: Vec<Constraint>,
+    pub steps: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathEnd {
+    Halted,
+    StepLimit,
+    /// The path needed a concrete value (address, jump target, opcode) it couldn't get.
+    Stuck(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Path {
+    pub state: SymbolicState,
+    pub end: PathEnd,
+}
+
+enum Flow {
+    Next,
+    Halt,
+    Fork(Vec<SymbolicState>),
+}
+
+impl SymbolicState {
+    pub fn new(program: &[i64]) -> Self {
+        SymbolicState {
+            memory: program.iter().map(|x| Rc::new(Expr::Const(*x))).collect(),
+            func_ptr: 0,
+            relative_base: 0,
+            inputs: 0,
+            outputs: vec![],
+            constraints: vec![],
+            steps: 0,
+        }
+    }
+
+    fn access(&self, addr: usize) -> Rc<Expr> {
+        match self.memory.get(addr) {
+            None => Rc::new(Expr::Const(0)),
+            Some(x) => x.clone(),
+        }
+    }
+
+    fn concrete(&self, addr: usize, what: &str) -> anyhow::Result<i64> {
+        let expr = self.access(addr);
+        expr.as_const().with_context(|| format!("Symbolic {what} {expr} at {addr}"))
+    }
+
+    fn address(&self, opcode: i64, offset: usize) -> anyhow::Result<usize> {
+        let param = self.func_ptr + offset;
+        let addr = match get_mode(opcode, offset)? {
+            Mode::IMMEDIATE => return Ok(param),
+            Mode::POSITION => self.concrete(param, "address")?,
+            Mode::RELATIVE => self.relative_base + self.concrete(param, "address")?,
+        };
+        usize::try_from(addr).with_context(|| format!("Negative address {addr} at {param}"))
+    }
+
+    fn read(&self, opcode: i64, offset: usize) -> anyhow::Result<Rc<Expr>> {
+        Ok(self.access(self.address(opcode, offset)?))
+    }
+
+    fn write(&mut self, opcode: i64, offset: usize, value: Rc<Expr>) -> anyhow::Result<()> {
+        if get_mode(opcode, offset)? == Mode::IMMEDIATE {
+            bail!("Can not write in Immediate mode");
+        }
+        let dest = self.address(opcode, offset)?;
+        if self.memory.len() <= dest {
+            self.memory.resize(dest + 1, Rc::new(Expr::Const(0)));
+        }
+        self.memory[dest] = value;
+        Ok(())
+    }
+
+    fn fork(&self, cond: Rc<Expr>, holds: bool) -> Self {
+        let mut state = self.clone();
+        state.constraints.push(Constraint { cond, holds });
+        state
+    }
+
+    /// Splits on every instruction the symbolic cell at `func_ptr` could select,
+    /// keeping the parameter modes written in the program.
+    fn fork_opcode(&self) -> anyhow::Result<Flow> {
+        let expr = self.access(self.func_ptr);
+        let lin = expr.linear().with_context(|| format!("Symbolic opcode {expr} at {}", self.func_ptr))?;
+        let modes = lin.b.div_euclid(100) * 100;
+        let forks = [1, 2, 3, 4, 5, 6, 7, 8, 9, 99]
+            .into_iter()
+            .map(|op| modes + op)
+            .filter(|k| lin.a != 0 && (k - lin.b).rem(lin.a) == 0)
+            .map(|k| {
+                let mut state = self.fork(Expr::equals(expr.clone(), Rc::new(Expr::Const(k))), true);
+                state.memory[self.func_ptr] = Rc::new(Expr::Const(k));
+                state
+            })
+            .collect_vec();
+        Ok(Flow::Fork(forks))
+    }
+
+    fn step(&mut self) -> anyhow::Result<Flow> {
+        let opcode = match self.access(self.func_ptr).as_const() {
+            Some(opcode) => opcode,
+            None => return self.fork_opcode(),
+        };
+        self.steps += 1;
+        match opcode.rem(100) {
+            op @ (1 | 2 | 7 | 8) => {
+                let x = self.read(opcode, 1)?;
+                let y = self.read(opcode, 2)?;
+                let res = match op {
+                    1 => Expr::sum(x, y),
+                    2 => Expr::product(x, y),
+                    7 => Expr::less_than(x, y),
+                    _ => Expr::equals(x, y),
+                };
+                self.write(opcode, 3, res)?;
+            }
+            3 => {
+                self.write(opcode, 1, Rc::new(Expr::Input(self.inputs)))?;
+                self.inputs += 1;
+            }
+            4 => {
+                let res = self.read(opcode, 1)?;
+                self.outputs.push(res);
+            }
+            op @ (5 | 6) => {
+                let cond = self.read(opcode, 1)?;
+                let target = self.address(opcode, 2)?;
+                let jump_on = op == 5;
+                let next = self.func_ptr + 3;
+                return match cond.as_const() {
+                    Some(c) => {
+                        self.func_ptr = if (c != 0) == jump_on { self.concrete(target, "jump target")? as usize } else { next };
+                        Ok(Flow::Next)
+                    }
+                    None => {
+                        let mut jump = self.fork(cond.clone(), jump_on);
+                        jump.func_ptr = jump.concrete(target, "jump target")? as usize;
+                        let mut fall = self.fork(cond, !jump_on);
+                        fall.func_ptr = next;
+                        Ok(Flow::Fork(vec![fall, jump]))
+                    }
+                };
+            }
+            9 => {
+                let addr = self.address(opcode, 1)?;
+                self.relative_base += self.concrete(addr, "relative base offset")?;
+            }
+            99 => return Ok(Flow::Halt),
+            _ => bail!("Bad instr {opcode} at {0}", self.func_ptr),
+        }
+        self.func_ptr += 1 + param_count(opcode)?;
+        Ok(Flow::Next)
+    }
+}
+
+/// Finds input values satisfying every constraint, trying values around the
+/// boundaries of the comparisons involved. This is a heuristic: it is exact for
+/// constraints that are linear in a single input, and may miss solutions otherwise.
+pub fn solve(constraints: &[Constraint], inputs: usize) -> Option<Vec<i64>> {
+    let mut found = vec![];
+    for c in constraints {
+        c.cond.candidates(&mut found);
+    }
+    let per_input = (0..inputs)
+        .map(|n| {
+            found.iter().filter(|(s, _)| *s == n).map(|(_, v)| *v).chain(std::iter::once(0)).unique().collect_vec()
+        })
+        .collect_vec();
+    if inputs == 0 {
+        return constraints.iter().all(|c| c.check(&[])).then_some(vec![]);
+    }
+    per_input
+        .into_iter()
+        .multi_cartesian_product()
+        .take(1_000_000)
+        .find(|values| constraints.iter().all(|c| c.check(values)))
+}
+
+/// Whether no inputs can satisfy `constraints`. Only answers `true` when every
+/// constraint is linear in the same single input, where [`solve`] is exact,
+/// so a branch it rules out really can't be taken.
+fn infeasible(constraints: &[Constraint], inputs: usize) -> bool {
+    let mut symbols = constraints.iter().map(|c| c.cond.exact_symbol());
+    let Some(symbols) = symbols.try_fold(vec![], |mut acc: Vec<usize>, symbol| {
+        acc.extend(symbol?);
+        Some(acc)
+    }) else {
+        return false;
+    };
+    symbols.iter().all_equal() && solve(constraints, inputs).is_none()
+}
+
+/// Explores every path through `program` with each input treated as a fresh symbol,
+/// forking at jumps and opcodes that depend on the inputs. Branches are only
+/// dropped once they're proven infeasible, so paths that can't actually be
+/// taken may be kept when the constraints are too complex to decide.
+pub fn explore(program: &[i64], max_steps: usize, max_paths: usize) -> Vec<Path> {
+    let mut pending = vec![SymbolicState::new(program)];
+    let mut paths: Vec<Path> = vec![];
+
+    'paths: while let Some(mut state) = pending.pop() {
+        if paths.len() >= max_paths {
+            break;
+        }
+        let end = loop {
+            if state.steps >= max_steps {
+                break PathEnd::StepLimit;
+            }
+            match state.step() {
+                Ok(Flow::Next) => {}
+                Ok(Flow::Halt) => break PathEnd::Halted,
+                Ok(Flow::Fork(forks)) => {
+                    pending.extend(forks.into_iter().filter(|s| !infeasible(&s.constraints, s.inputs)));
+                    continue 'paths;
+                }
+                Err(e) => break PathEnd::Stuck(e.to_string()),
+            }
+        };
+        log::debug!("Path ended {end:?} after {} steps", state.steps);
+        paths.push(Path { state, end });
+    }
+
+    paths
+}
+
+/// Finds inputs that make a halting path's last output equal `target`.
+pub fn find_input(paths: &[Path], target: i64) -> Option<(Vec<i64>, &Path)> {
+    paths.iter().filter(|p| p.end == PathEnd::Halted).find_map(|p| {
+        let output = p.state.outputs.last()?.clone();
+        let mut constraints = p.state.constraints.clone();
+        constraints.push(Constraint { cond: Expr::equals(output, Rc::new(Expr::Const(target))), holds: true });
+        Some((solve(&constraints, p.state.inputs)?, p))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::{process, ProgramState};
+    use crate::loader::{load, Source};
+
+    fn program(input: &str) -> Vec<i64> {
+        load(Source::Str(input)).unwrap()
+    }
+
+    /// Every output of `program` run by the concrete interpreter on `input`.
+    fn concrete(program: &[i64], input: i64) -> Vec<i64> {
+        let mut state = ProgramState { memory: program.to_vec(), input, ..Default::default() };
+        let mut outputs = vec![];
+        while let Some(output) = process(&mut state).unwrap() {
+            outputs.push(output);
+        }
+        outputs
+    }
+
+    /// Outputs of the halting path `inputs` would take.
+    fn symbolic(paths: &[Path], inputs: &[i64]) -> Vec<i64> {
+        let path = paths
+            .iter()
+            .find(|p| p.end == PathEnd::Halted && p.state.constraints.iter().all(|c| c.check(inputs)))
+            .expect("a halting path for the inputs");
+        path.state.outputs.iter().map(|o| o.eval(inputs)).collect()
+    }
+
+    #[test]
+    fn day5_matches_interpreter() {
+        let program = program(include_str!("../inputs/input-05-2019.txt"));
+        let mut expected = vec![0; 9];
+        expected.push(13787043);
+        assert_eq!(concrete(&program, 1), expected);
+        assert_eq!(concrete(&program, 5), [3892695]);
+
+        let paths = explore(&program, 1_000_000, 16);
+        assert_eq!(symbolic(&paths, &[1]), expected);
+        assert_eq!(symbolic(&paths, &[5]), [3892695]);
+
+        let (inputs, _) = find_input(&paths, 3892695).unwrap();
+        assert_eq!(concrete(&program, inputs[0]).last(), Some(&3892695));
+    }
+
+    #[test]
+    fn day9_matches_interpreter() {
+        let program = program(include_str!("../inputs/input-09-2019.txt"));
+        let paths = explore(&program, 1_000_000, 16);
+        for mode in [1, 2] {
+            let expected = concrete(&program, mode);
+            assert_eq!(symbolic(&paths, &[mode]), expected);
+
+            let target = *expected.last().unwrap();
+            let (inputs, _) = find_input(&paths, target).unwrap();
+            assert_eq!(concrete(&program, inputs[0]).last(), Some(&target));
+        }
+    }
+
+    #[test]
+    fn solves_linear_constraints() {
+        let input = Rc::new(Expr::Input(0));
+        let doubled = Expr::product(input.clone(), Rc::new(Expr::Const(2)));
+        let constraints = [
+            Constraint { cond: Expr::less_than(Rc::new(Expr::Const(10)), doubled), holds: true },
+            Constraint { cond: Expr::less_than(input, Rc::new(Expr::Const(7))), holds: true },
+        ];
+        let inputs = solve(&constraints, 1).unwrap();
+        assert!(constraints.iter().all(|c| c.check(&inputs)));
+        assert_eq!(solve(&constraints[..1], 0), None);
+    }
+
+    #[test]
+    fn prunes_only_proven_infeasible_branches() {
+        let input = Rc::new(Expr::Input(0));
+        let is = |value: i64, holds: bool| Constraint { cond: Expr::equals(input.clone(), Rc::new(Expr::Const(value))), holds };
+        assert!(infeasible(&[is(1, true), is(5, true)], 1));
+        assert!(!infeasible(&[is(1, true), is(5, false)], 1));
+
+        // Squaring isn't linear, so solve's failure to find 1000 * 1000 proves nothing.
+        let square = Expr::product(input.clone(), input.clone());
+        let big = [Constraint { cond: Expr::equals(square, Rc::new(Expr::Const(1_000_000))), holds: true }];
+        assert_eq!(solve(&big, 1), None);
+        assert!(!infeasible(&big, 1));
+    }
+}